@@ -1,6 +1,10 @@
-use anyhow::Result;
+use std::cmp::Ordering;
+use std::collections::VecDeque;
+use std::io::BufRead;
 
-use crate::runner::{Parse, Run};
+use anyhow::{bail, Result};
+
+use crate::runner::{Parse, Run, RunStream};
 
 pub struct Day1 {}
 
@@ -11,39 +15,90 @@ impl Parse<Vec<i32>> for Day1 {
 }
 impl Run<Vec<i32>, i32> for Day1 {
     fn part_one(input: &Vec<i32>) -> Result<i32> {
-        Ok(count_increased(input))
+        Ok(count_changes(input.iter().copied(), 1, Change::Increase)?.try_into()?)
     }
 
     fn part_two(input: &Vec<i32>) -> Result<i32> {
-        Ok(count_increased(&clean_measures(input)))
+        Ok(count_changes(input.iter().copied(), 3, Change::Increase)?.try_into()?)
     }
 }
 
-fn count_increased(measures: &[i32]) -> i32 {
-    let mut count = -1;
-    measures.iter().fold(0, |acc, m| {
-        if m > &acc {
-            count += 1;
+impl RunStream<i32> for Day1 {
+    fn run_lines(input: impl BufRead) -> Result<(i32, i32)> {
+        let mut one = WindowCounter::new(1, Change::Increase)?;
+        let mut three = WindowCounter::new(3, Change::Increase)?;
+        for line in input.lines() {
+            if let Ok(m) = line?.parse() {
+                one.push(m);
+                three.push(m);
+            }
         }
-        *m
-    });
-    count
+        Ok((one.count.try_into()?, three.count.try_into()?))
+    }
+}
+
+/// How a window compares with the previous one
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Change {
+    Increase,
+    Decrease,
+    Unchanged,
+}
+
+/// Counts the windows of `window` measures whose sum changes as `change` from the previous one.
+/// Consecutive windows share all but one measure, so comparing the sums is the same as
+/// comparing the measure coming in with the one leaving, only those have to be kept
+pub fn count_changes(
+    measures: impl IntoIterator<Item = i32>,
+    window: usize,
+    change: Change,
+) -> Result<usize> {
+    let mut counter = WindowCounter::new(window, change)?;
+    measures.into_iter().for_each(|m| counter.push(m));
+    Ok(counter.count)
 }
 
-fn clean_measures(measures: &[i32]) -> Vec<i32> {
-    let mut new_measurements = Vec::new();
-    for (i, m) in measures.iter().enumerate() {
-        if i + 2 < measures.len() {
-            new_measurements.push(m + measures[i + 1] + measures[i + 2])
+// Keeps the last `window` measures to compare with the next one
+struct WindowCounter {
+    window: usize,
+    change: Change,
+    last: VecDeque<i32>,
+    count: usize,
+}
+
+impl WindowCounter {
+    fn new(window: usize, change: Change) -> Result<Self> {
+        if window == 0 {
+            bail!("window has to be at least one measure");
+        }
+        Ok(Self {
+            window,
+            change,
+            last: VecDeque::with_capacity(window),
+            count: 0,
+        })
+    }
+
+    fn push(&mut self, m: i32) {
+        if self.last.len() == self.window {
+            let leaving = self.last.pop_front().unwrap_or_default();
+            let current = match m.cmp(&leaving) {
+                Ordering::Greater => Change::Increase,
+                Ordering::Less => Change::Decrease,
+                Ordering::Equal => Change::Unchanged,
+            };
+            if current == self.change {
+                self.count += 1;
+            }
         }
+        self.last.push_back(m);
     }
-    new_measurements
 }
 
 #[cfg(test)]
 mod tests_day1 {
     use super::*;
-    use crate::runner::Executor;
+    use crate::runner::{Executor, StreamExecutor};
     use anyhow::Result;
 
     #[test]
@@ -55,20 +110,41 @@ mod tests_day1 {
     }
 
     #[test]
-    fn test_count_increased() {
+    fn test_run_stream() -> Result<()> {
+        let (r1, r2) = Day1::run_stream("inputs/day1.test")?;
+        assert_eq!(r1, 7);
+        assert_eq!(r2, 5);
+        Ok(())
+    }
+
+    #[test]
+    fn test_count_increased() -> Result<()> {
         let measures = vec![199, 200, 208, 210, 200, 207, 240, 269, 260, 263];
-        let count = count_increased(&measures);
+        let count = count_changes(measures, 1, Change::Increase)?;
         assert_eq!(7, count);
+        Ok(())
     }
 
     #[test]
-    fn test_clean_measures() {
+    fn test_count_windows() -> Result<()> {
         let measures = vec![199, 200, 208, 210, 200, 207, 240, 269, 260, 263];
-        let count = clean_measures(&measures);
-        assert_eq!(8, count.len());
-        let mut it = count.into_iter();
-        assert_eq!(607, it.next().unwrap());
-        assert_eq!(618, it.next().unwrap());
-        assert_eq!(792, it.last().unwrap());
+        let count = |window, change| count_changes(measures.iter().copied(), window, change);
+        assert_eq!(count(3, Change::Increase)?, 5);
+        assert_eq!(count(3, Change::Decrease)?, 1);
+        assert_eq!(count(3, Change::Unchanged)?, 1);
+        assert_eq!(count(1, Change::Decrease)?, 2);
+        assert_eq!(count(10, Change::Increase)?, 0);
+        assert_eq!(count(11, Change::Increase)?, 0);
+        assert!(count(0, Change::Increase).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_count_negative_depths() -> Result<()> {
+        let measures = vec![-5, -3, -3, -10, 0];
+        assert_eq!(count_changes(measures.clone(), 1, Change::Increase)?, 2);
+        assert_eq!(count_changes(measures.clone(), 1, Change::Unchanged)?, 1);
+        assert_eq!(count_changes(measures, 2, Change::Decrease)?, 1);
+        Ok(())
     }
 }
@@ -0,0 +1,144 @@
+use anyhow::{bail, Result};
+
+use crate::runner::{Parse, Run};
+
+pub struct Day10 {}
+
+impl Parse<Vec<String>> for Day10 {
+    fn parse_input(input: &str) -> Result<Vec<String>> {
+        let r = input
+            .lines()
+            .map(str::trim)
+            .filter(|l| !l.is_empty())
+            .map(String::from)
+            .collect();
+        Ok(r)
+    }
+}
+
+impl Run<Vec<String>, u64> for Day10 {
+    fn part_one(input: &Vec<String>) -> Result<u64> {
+        input.iter().try_fold(0, |score, line| {
+            Ok(match check_line(line)? {
+                Line::Corrupted(c) => score + error_score(c),
+                Line::Incomplete(_) => score,
+            })
+        })
+    }
+
+    fn part_two(input: &Vec<String>) -> Result<u64> {
+        let mut scores = Vec::new();
+        for line in input {
+            if let Line::Incomplete(missing) = check_line(line)? {
+                scores.push(completion_score(&missing)?);
+            }
+        }
+        if scores.is_empty() {
+            bail!("no incomplete lines");
+        }
+        scores.sort_unstable();
+        Ok(scores[scores.len() / 2])
+    }
+}
+
+/// What is wrong with a line of chunks
+#[derive(Debug, PartialEq, Eq)]
+pub enum Line {
+    /// First closing character that doesn't match its opening one
+    Corrupted(char),
+    /// Closing characters that complete the line
+    Incomplete(String),
+}
+
+fn closing(c: char) -> Option<char> {
+    match c {
+        '(' => Some(')'),
+        '[' => Some(']'),
+        '{' => Some('}'),
+        '<' => Some('>'),
+        _ => None,
+    }
+}
+
+pub fn check_line(line: &str) -> Result<Line> {
+    let mut expected = Vec::new();
+    for c in line.chars() {
+        match (closing(c), c) {
+            (Some(close), _) => expected.push(close),
+            (None, ')' | ']' | '}' | '>') => {
+                if expected.pop() != Some(c) {
+                    return Ok(Line::Corrupted(c));
+                }
+            }
+            _ => bail!("invalid character {:?} in {}", c, line),
+        }
+    }
+    Ok(Line::Incomplete(expected.into_iter().rev().collect()))
+}
+
+fn error_score(c: char) -> u64 {
+    match c {
+        ')' => 3,
+        ']' => 57,
+        '}' => 1197,
+        _ => 25137,
+    }
+}
+
+// Fails once the score doesn't fit on a u64, from around 28 missing characters
+fn completion_score(missing: &str) -> Result<u64> {
+    missing.chars().try_fold(0u64, |score, c| {
+        let points = match c {
+            ')' => 1,
+            ']' => 2,
+            '}' => 3,
+            _ => 4,
+        };
+        score
+            .checked_mul(5)
+            .and_then(|score| score.checked_add(points))
+            .ok_or(anyhow::anyhow!(
+                "completion score of {:?} overflows a u64",
+                missing
+            ))
+    })
+}
+
+#[cfg(test)]
+mod tests_day10 {
+    use super::*;
+    use crate::runner::Executor;
+
+    #[test]
+    fn test_run() -> Result<()> {
+        let (r1, r2) = Day10::run("inputs/day10.test")?;
+        assert_eq!(r1, 26397);
+        assert_eq!(r2, 288957);
+        Ok(())
+    }
+
+    #[test]
+    fn test_check_line() -> Result<()> {
+        assert_eq!(
+            check_line("{([(<{}[<>[]}>{[]{[(<()>")?,
+            Line::Corrupted('}')
+        );
+        assert_eq!(
+            check_line("[({(<(())[]>[[{[]{<()<>>")?,
+            Line::Incomplete("}}]])})]".to_string())
+        );
+        assert_eq!(check_line("()")?, Line::Incomplete(String::new()));
+        assert!(check_line("(a)").is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_completion_score() -> Result<()> {
+        assert_eq!(completion_score("])}>")?, 294);
+        assert_eq!(completion_score("}}]])})]")?, 288957);
+        assert_eq!(completion_score(&")".repeat(27))?, (5u64.pow(27) - 1) / 4);
+        let lines = vec!["(".repeat(40)];
+        assert!(Day10::part_two(&lines).is_err());
+        Ok(())
+    }
+}
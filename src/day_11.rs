@@ -0,0 +1,96 @@
+use anyhow::{bail, Result};
+
+use crate::grid::{Connectivity, Grid};
+use crate::runner::{Parse, RunMut};
+
+pub struct Day11 {}
+
+impl Parse<Grid<u32>> for Day11 {
+    fn parse_input(input: &str) -> Result<Grid<u32>> {
+        Grid::parse_digits(input)
+    }
+}
+
+impl RunMut<Grid<u32>, usize> for Day11 {
+    fn part_one(input: &mut Grid<u32>) -> Result<usize> {
+        Ok((0..100).map(|_| step(input)).sum())
+    }
+
+    fn part_two(input: &mut Grid<u32>) -> Result<usize> {
+        first_sync(input, MAX_STEPS)
+    }
+}
+
+/// Steps tried before giving up on the octopuses ever flashing together
+const MAX_STEPS: usize = 100_000;
+
+/// First step where every octopus flashes at once, within `limit` steps
+pub fn first_sync(octopuses: &mut Grid<u32>, limit: usize) -> Result<usize> {
+    let total = octopuses.width() * octopuses.height();
+    match (1..=limit).find(|_| step(octopuses) == total) {
+        Some(steps) => Ok(steps),
+        None => bail!("the octopuses don't flash together within {} steps", limit),
+    }
+}
+
+/// Raises every energy level and lets the octopuses flash, returning how many did
+pub fn step(octopuses: &mut Grid<u32>) -> usize {
+    let mut flashing = Vec::new();
+    for pos in octopuses.positions() {
+        octopuses[pos] += 1;
+        if octopuses[pos] == 10 {
+            flashing.push(pos);
+        }
+    }
+    let mut flashes = 0;
+    while let Some(pos) = flashing.pop() {
+        flashes += 1;
+        for n in octopuses
+            .neighbours(pos, Connectivity::Eight)
+            .collect::<Vec<_>>()
+        {
+            octopuses[n] += 1;
+            if octopuses[n] == 10 {
+                flashing.push(n);
+            }
+        }
+    }
+    for pos in octopuses.positions() {
+        if octopuses[pos] > 9 {
+            octopuses[pos] = 0;
+        }
+    }
+    flashes
+}
+
+#[cfg(test)]
+mod tests_day11 {
+    use super::*;
+    use crate::runner::MutExecutor;
+
+    #[test]
+    fn test_run() -> Result<()> {
+        let (r1, r2) = Day11::run("inputs/day11.test")?;
+        assert_eq!(r1, 1656);
+        assert_eq!(r2, 195);
+        Ok(())
+    }
+
+    #[test]
+    fn test_step() -> Result<()> {
+        let mut octopuses = Grid::parse_digits("11111\n19991\n19191\n19991\n11111\n")?;
+        assert_eq!(step(&mut octopuses), 9);
+        assert_eq!(octopuses.to_string(), "34543\n40004\n50005\n40004\n34543\n");
+        assert_eq!(step(&mut octopuses), 0);
+        assert_eq!(octopuses.to_string(), "45654\n51115\n61116\n51115\n45654\n");
+        Ok(())
+    }
+
+    #[test]
+    fn test_first_sync() -> Result<()> {
+        let mut octopuses = Day11::parse_input(include_str!("../inputs/day11.test"))?;
+        assert!(first_sync(&mut octopuses.clone(), 194).is_err());
+        assert_eq!(first_sync(&mut octopuses, 195)?, 195);
+        Ok(())
+    }
+}
@@ -0,0 +1,123 @@
+use std::collections::HashMap;
+
+use anyhow::{bail, Result};
+
+use crate::runner::{Parse, Run};
+
+pub struct Day12 {}
+
+impl Parse<Caves> for Day12 {
+    fn parse_input(input: &str) -> Result<Caves> {
+        Caves::parse(input)
+    }
+}
+
+impl Run<Caves, usize> for Day12 {
+    fn part_one(input: &Caves) -> Result<usize> {
+        Ok(input.count_paths(false))
+    }
+
+    fn part_two(input: &Caves) -> Result<usize> {
+        Ok(input.count_paths(true))
+    }
+}
+
+/// Cave system, caves are numbered in the order they appear
+#[derive(Debug, Clone)]
+pub struct Caves {
+    names: Vec<String>,
+    links: Vec<Vec<usize>>,
+    start: usize,
+    end: usize,
+}
+
+impl Caves {
+    /// Parses one `a-b` passage per line, there must be a start and an end
+    pub fn parse(input: &str) -> Result<Self> {
+        let mut ids: HashMap<&str, usize> = HashMap::new();
+        let mut names = Vec::new();
+        let mut links: Vec<Vec<usize>> = Vec::new();
+        for line in input.lines().map(str::trim).filter(|l| !l.is_empty()) {
+            let (a, b) = match line.split_once('-') {
+                Some(link) => link,
+                None => bail!("invalid passage {:?}", line),
+            };
+            let [a, b] = [a, b].map(|name| {
+                *ids.entry(name).or_insert_with(|| {
+                    names.push(name.to_string());
+                    links.push(Vec::new());
+                    names.len() - 1
+                })
+            });
+            links[a].push(b);
+            links[b].push(a);
+        }
+        if names.len() > 64 {
+            bail!("too many caves: {}", names.len());
+        }
+        let (start, end) = match (ids.get("start"), ids.get("end")) {
+            (Some(start), Some(end)) => (*start, *end),
+            _ => bail!("missing start or end cave"),
+        };
+        Ok(Self {
+            names,
+            links,
+            start,
+            end,
+        })
+    }
+
+    fn is_small(&self, cave: usize) -> bool {
+        self.names[cave].chars().all(|c| c.is_ascii_lowercase())
+    }
+
+    /// Paths from start to end visiting small caves once, and one of them
+    /// twice if `revisit` is set
+    pub fn count_paths(&self, revisit: bool) -> usize {
+        self.paths_from(self.start, 1 << self.start, revisit)
+    }
+
+    // Visited small caves are kept as a bit set
+    fn paths_from(&self, cave: usize, visited: u64, revisit: bool) -> usize {
+        if cave == self.end {
+            return 1;
+        }
+        self.links[cave]
+            .iter()
+            .map(
+                |next| match (self.is_small(*next), visited & 1 << next != 0) {
+                    (false, _) => self.paths_from(*next, visited, revisit),
+                    (true, false) => self.paths_from(*next, visited | 1 << next, revisit),
+                    (true, true) if revisit && *next != self.start => {
+                        self.paths_from(*next, visited, false)
+                    }
+                    (true, true) => 0,
+                },
+            )
+            .sum()
+    }
+}
+
+#[cfg(test)]
+mod tests_day12 {
+    use super::*;
+    use crate::runner::Executor;
+
+    #[test]
+    fn test_run() -> Result<()> {
+        let (r1, r2) = Day12::run("inputs/day12.test")?;
+        assert_eq!(r1, 19);
+        assert_eq!(r2, 103);
+        Ok(())
+    }
+
+    #[test]
+    fn test_small_example() -> Result<()> {
+        let caves = Caves::parse("start-A\nstart-b\nA-c\nA-b\nb-d\nA-end\nb-end\n")?;
+        assert_eq!(caves.count_paths(false), 10);
+        assert_eq!(caves.count_paths(true), 36);
+        assert!(Caves::parse("start-A\nA-b\n").is_err());
+        assert!(Caves::parse("start end\n").is_err());
+        Ok(())
+    }
+}
@@ -0,0 +1,120 @@
+use std::collections::BTreeSet;
+
+use anyhow::{bail, Result};
+
+use crate::runner::{Parse, Run};
+
+pub struct Day13 {}
+
+impl Parse<Paper> for Day13 {
+    fn parse_input(input: &str) -> Result<Paper> {
+        let mut dots = BTreeSet::new();
+        let mut folds = Vec::new();
+        for line in input.lines().map(str::trim).filter(|l| !l.is_empty()) {
+            if let Some(fold) = line.strip_prefix("fold along ") {
+                folds.push(match fold.split_once('=') {
+                    Some(("x", x)) => Fold::Left(x.parse()?),
+                    Some(("y", y)) => Fold::Up(y.parse()?),
+                    _ => bail!("invalid fold {:?}", line),
+                });
+                continue;
+            }
+            match line.split_once(',') {
+                Some((x, y)) => dots.insert((x.parse()?, y.parse()?)),
+                None => bail!("invalid dot {:?}", line),
+            };
+        }
+        Ok(Paper { dots, folds })
+    }
+}
+
+// Part two is the code drawn on the paper, so both parts are text
+impl Run<Paper, String> for Day13 {
+    fn part_one(input: &Paper) -> Result<String> {
+        let fold = input.folds.first().ok_or(anyhow::anyhow!("no folds"))?;
+        Ok(fold_dots(&input.dots, fold)?.len().to_string())
+    }
+
+    fn part_two(input: &Paper) -> Result<String> {
+        let dots = input
+            .folds
+            .iter()
+            .try_fold(input.dots.clone(), |dots, fold| fold_dots(&dots, fold))?;
+        Ok(format!("\n{}", render(&dots)))
+    }
+}
+
+/// Dots as (x, y) and the folds in order
+#[derive(Debug, Clone)]
+pub struct Paper {
+    dots: BTreeSet<(u32, u32)>,
+    folds: Vec<Fold>,
+}
+
+/// Line the paper is folded along
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Fold {
+    /// Vertical line at x, the right half goes over the left one
+    Left(u32),
+    /// Horizontal line at y, the bottom half goes over the top one
+    Up(u32),
+}
+
+/// Dots left after the fold, a dot that would land past the edge of the
+/// paper is an error
+pub fn fold_dots(dots: &BTreeSet<(u32, u32)>, fold: &Fold) -> Result<BTreeSet<(u32, u32)>> {
+    let mirror = |v: u32, line: u32| match v > line {
+        true => line.checked_sub(v - line).ok_or(anyhow::anyhow!(
+            "dot at {} folds off the paper along {}",
+            v,
+            line
+        )),
+        false => Ok(v),
+    };
+    dots.iter()
+        .map(|(x, y)| match fold {
+            Fold::Left(line) => Ok((mirror(*x, *line)?, *y)),
+            Fold::Up(line) => Ok((*x, mirror(*y, *line)?)),
+        })
+        .collect()
+}
+
+/// Draws the dots with `#` and the rest with `.`, one row per line
+pub fn render(dots: &BTreeSet<(u32, u32)>) -> String {
+    let width = dots.iter().map(|(x, _)| x + 1).max().unwrap_or(0);
+    let height = dots.iter().map(|(_, y)| y + 1).max().unwrap_or(0);
+    let mut drawing = String::new();
+    for y in 0..height {
+        (0..width).for_each(|x| drawing.push(if dots.contains(&(x, y)) { '#' } else { '.' }));
+        drawing.push('\n');
+    }
+    drawing
+}
+
+#[cfg(test)]
+mod tests_day13 {
+    use super::*;
+    use crate::runner::Executor;
+
+    #[test]
+    fn test_run() -> Result<()> {
+        let (r1, r2) = Day13::run("inputs/day13.test")?;
+        assert_eq!(r1, "17");
+        assert_eq!(r2, "\n#####\n#...#\n#...#\n#...#\n#####\n");
+        Ok(())
+    }
+
+    #[test]
+    fn test_fold_dots() -> Result<()> {
+        let dots = BTreeSet::from([(0, 0), (0, 4), (3, 1)]);
+        let folded = fold_dots(&dots, &Fold::Up(2))?;
+        assert_eq!(folded, BTreeSet::from([(0, 0), (3, 1)]));
+        let folded = fold_dots(&folded, &Fold::Left(2))?;
+        assert_eq!(folded, BTreeSet::from([(0, 0), (1, 1)]));
+        assert_eq!(render(&folded), "#.\n.#\n");
+        // Folding at 1 would send the dot at 3 to -1
+        assert!(fold_dots(&dots, &Fold::Left(1)).is_err());
+        assert!(fold_dots(&BTreeSet::from([(0, u32::MAX)]), &Fold::Up(u32::MAX - 1)).is_ok());
+        Ok(())
+    }
+}
@@ -0,0 +1,120 @@
+use std::collections::HashMap;
+
+use anyhow::{bail, Result};
+
+use crate::runner::{Parse, Run};
+
+pub struct Day14 {}
+
+impl Parse<Polymer> for Day14 {
+    fn parse_input(input: &str) -> Result<Polymer> {
+        let mut lines = input.lines().map(str::trim).filter(|l| !l.is_empty());
+        let template: Vec<char> = match lines.next() {
+            Some(template) => template.chars().collect(),
+            None => bail!("missing template"),
+        };
+        let mut rules = HashMap::new();
+        for line in lines {
+            let rule = line.split_once(" -> ").and_then(|(pair, element)| {
+                let mut pair = pair.chars();
+                let mut element = element.chars();
+                match (pair.next(), pair.next(), pair.next(), element.next()) {
+                    (Some(a), Some(b), None, Some(c)) => Some(((a, b), c)),
+                    _ => None,
+                }
+            });
+            match rule {
+                Some((pair, element)) => rules.insert(pair, element),
+                None => bail!("invalid rule {:?}", line),
+            };
+        }
+        Ok(Polymer { template, rules })
+    }
+}
+
+impl Run<Polymer, u64> for Day14 {
+    fn part_one(input: &Polymer) -> Result<u64> {
+        input.spread(10)
+    }
+
+    fn part_two(input: &Polymer) -> Result<u64> {
+        input.spread(40)
+    }
+}
+
+/// Polymer template with its pair insertion rules
+#[derive(Debug, Clone)]
+pub struct Polymer {
+    template: Vec<char>,
+    rules: HashMap<(char, char), char>,
+}
+
+impl Polymer {
+    /// Counts of each element after the steps, only the pairs are tracked so
+    /// the length of the polymer doesn't matter
+    pub fn elements(&self, steps: usize) -> HashMap<char, u64> {
+        let mut pairs: HashMap<(char, char), u64> = HashMap::new();
+        self.template
+            .windows(2)
+            .for_each(|w| *pairs.entry((w[0], w[1])).or_default() += 1);
+        for _ in 0..steps {
+            let mut next = HashMap::new();
+            for ((a, b), count) in pairs {
+                match self.rules.get(&(a, b)) {
+                    Some(c) => {
+                        *next.entry((a, *c)).or_default() += count;
+                        *next.entry((*c, b)).or_default() += count;
+                    }
+                    None => *next.entry((a, b)).or_default() += count,
+                }
+            }
+            pairs = next;
+        }
+        // Every element is the first of a pair but the last one of the template
+        let mut elements: HashMap<char, u64> = HashMap::new();
+        pairs
+            .iter()
+            .for_each(|((a, _), count)| *elements.entry(*a).or_default() += count);
+        if let Some(last) = self.template.last() {
+            *elements.entry(*last).or_default() += 1;
+        }
+        elements
+    }
+
+    /// Most common element count minus the least common one
+    pub fn spread(&self, steps: usize) -> Result<u64> {
+        let elements = self.elements(steps);
+        match (elements.values().max(), elements.values().min()) {
+            (Some(max), Some(min)) => Ok(max - min),
+            _ => bail!("empty polymer"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests_day14 {
+    use super::*;
+    use crate::runner::Executor;
+
+    #[test]
+    fn test_run() -> Result<()> {
+        let (r1, r2) = Day14::run("inputs/day14.test")?;
+        assert_eq!(r1, 1588);
+        assert_eq!(r2, 2188189693529);
+        Ok(())
+    }
+
+    #[test]
+    fn test_elements() -> Result<()> {
+        let polymer = Day14::parse_input(include_str!("../inputs/day14.test"))?;
+        // NCNBCHB after one step
+        let elements = polymer.elements(1);
+        assert_eq!(elements[&'N'], 2);
+        assert_eq!(elements[&'C'], 2);
+        assert_eq!(elements[&'B'], 2);
+        assert_eq!(elements[&'H'], 1);
+        assert_eq!(polymer.elements(10).values().sum::<u64>(), 3073);
+        assert!(Day14::parse_input("NN\n\nNNN -> C\n").is_err());
+        Ok(())
+    }
+}
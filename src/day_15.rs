@@ -0,0 +1,91 @@
+use std::cmp::Reverse;
+use std::collections::BinaryHeap;
+
+use anyhow::Result;
+
+use crate::grid::{Connectivity, Grid};
+use crate::runner::{Parse, Run};
+
+pub struct Day15 {}
+
+impl Parse<Grid<u32>> for Day15 {
+    fn parse_input(input: &str) -> Result<Grid<u32>> {
+        Grid::parse_digits(input)
+    }
+}
+
+impl Run<Grid<u32>, u32> for Day15 {
+    fn part_one(input: &Grid<u32>) -> Result<u32> {
+        lowest_risk(input).ok_or(anyhow::anyhow!("empty cave"))
+    }
+
+    fn part_two(input: &Grid<u32>) -> Result<u32> {
+        lowest_risk(&tile(input, 5)?).ok_or(anyhow::anyhow!("empty cave"))
+    }
+}
+
+/// Lowest total risk from the top left to the bottom right, the risk of the
+/// starting position doesn't count
+pub fn lowest_risk(cave: &Grid<u32>) -> Option<u32> {
+    let end = (cave.height().checked_sub(1)?, cave.width().checked_sub(1)?);
+    let mut risks = Grid::filled(cave.width(), cave.height(), u32::MAX);
+    let mut queue = BinaryHeap::from([Reverse((0, (0, 0)))]);
+    risks[(0, 0)] = 0;
+    while let Some(Reverse((risk, pos))) = queue.pop() {
+        if pos == end {
+            return Some(risk);
+        }
+        if risk > risks[pos] {
+            continue;
+        }
+        for next in cave.neighbours(pos, Connectivity::Four) {
+            let total = risk + cave[next];
+            if total < risks[next] {
+                risks[next] = total;
+                queue.push(Reverse((total, next)));
+            }
+        }
+    }
+    None
+}
+
+/// Repeats the cave `times` in both directions, each tile to the right or
+/// down has its risks one higher, wrapping from 9 back to 1
+pub fn tile(cave: &Grid<u32>, times: usize) -> Result<Grid<u32>> {
+    let rows = (0..cave.height() * times)
+        .map(|row| {
+            (0..cave.width() * times)
+                .map(|column| {
+                    let risk = cave[(row % cave.height(), column % cave.width())];
+                    let extra = (row / cave.height() + column / cave.width()) as u32;
+                    (risk + extra - 1) % 9 + 1
+                })
+                .collect()
+        })
+        .collect();
+    Grid::from_rows(rows)
+}
+
+#[cfg(test)]
+mod tests_day15 {
+    use super::*;
+    use crate::runner::Executor;
+
+    #[test]
+    fn test_run() -> Result<()> {
+        let (r1, r2) = Day15::run("inputs/day15.test")?;
+        assert_eq!(r1, 40);
+        assert_eq!(r2, 315);
+        Ok(())
+    }
+
+    #[test]
+    fn test_tile() -> Result<()> {
+        let cave = Grid::parse_digits("8\n")?;
+        assert_eq!(tile(&cave, 3)?.to_string(), "891\n912\n123\n");
+        // Going around the wall is cheaper
+        let cave = Grid::parse_digits("119\n191\n111\n")?;
+        assert_eq!(lowest_risk(&cave), Some(4));
+        Ok(())
+    }
+}
@@ -0,0 +1,251 @@
+use anyhow::{bail, Result};
+
+use crate::runner::{Parse, Run};
+
+pub struct Day16 {}
+
+impl Parse<Packet> for Day16 {
+    fn parse_input(input: &str) -> Result<Packet> {
+        let bits = hex_to_bits(input.trim())?;
+        let mut reader = BitReader { bits: &bits, at: 0 };
+        reader.packet()
+    }
+}
+
+impl Run<Packet, u64> for Day16 {
+    fn part_one(input: &Packet) -> Result<u64> {
+        Ok(input.version_sum())
+    }
+
+    fn part_two(input: &Packet) -> Result<u64> {
+        input.value()
+    }
+}
+
+/// BITS packet, literal values or operators over the packets inside them
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Packet {
+    version: u8,
+    content: Content,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Content {
+    Literal(u64),
+    Operator(u8, Vec<Packet>),
+}
+
+impl Packet {
+    /// Sum of the versions of this packet and every packet inside
+    pub fn version_sum(&self) -> u64 {
+        self.version as u64
+            + match &self.content {
+                Content::Literal(_) => 0,
+                Content::Operator(_, packets) => packets.iter().map(Packet::version_sum).sum(),
+            }
+    }
+
+    /// Evaluates the expression of the packet
+    pub fn value(&self) -> Result<u64> {
+        let (kind, packets) = match &self.content {
+            Content::Literal(value) => return Ok(*value),
+            Content::Operator(kind, packets) => (*kind, packets),
+        };
+        let values = packets
+            .iter()
+            .map(Packet::value)
+            .collect::<Result<Vec<_>>>()?;
+        let compare = |f: fn(&u64, &u64) -> bool| match values.as_slice() {
+            [a, b] => Ok(f(a, b) as u64),
+            _ => bail!("comparison of {} packets", values.len()),
+        };
+        match kind {
+            0 => values
+                .iter()
+                .try_fold(0u64, |sum, v| sum.checked_add(*v))
+                .ok_or(anyhow::anyhow!("sum overflows a u64")),
+            1 => values
+                .iter()
+                .try_fold(1u64, |product, v| product.checked_mul(*v))
+                .ok_or(anyhow::anyhow!("product overflows a u64")),
+            2 => values
+                .iter()
+                .min()
+                .copied()
+                .ok_or(anyhow::anyhow!("empty minimum")),
+            3 => values
+                .iter()
+                .max()
+                .copied()
+                .ok_or(anyhow::anyhow!("empty maximum")),
+            5 => compare(u64::gt),
+            6 => compare(u64::lt),
+            7 => compare(u64::eq),
+            kind => bail!("unknown operator {}", kind),
+        }
+    }
+}
+
+fn hex_to_bits(hex: &str) -> Result<Vec<bool>> {
+    let mut bits = Vec::with_capacity(hex.len() * 4);
+    for c in hex.chars() {
+        let digit = c
+            .to_digit(16)
+            .ok_or(anyhow::anyhow!("invalid hex digit {:?}", c))?;
+        (0..4).rev().for_each(|i| bits.push(digit >> i & 1 == 1));
+    }
+    Ok(bits)
+}
+
+struct BitReader<'a> {
+    bits: &'a [bool],
+    at: usize,
+}
+
+impl BitReader<'_> {
+    fn read(&mut self, count: usize) -> Result<u64> {
+        if self.at + count > self.bits.len() {
+            bail!("packet ends at bit {}", self.bits.len());
+        }
+        let value = self.bits[self.at..self.at + count]
+            .iter()
+            .fold(0, |value, bit| value << 1 | *bit as u64);
+        self.at += count;
+        Ok(value)
+    }
+
+    fn packet(&mut self) -> Result<Packet> {
+        let version = self.read(3)? as u8;
+        let content = match self.read(3)? as u8 {
+            4 => {
+                let mut value: u64 = 0;
+                loop {
+                    let more = self.read(1)? == 1;
+                    if value >> 60 != 0 {
+                        bail!("literal wider than 64 bits");
+                    }
+                    value = value << 4 | self.read(4)?;
+                    if !more {
+                        break;
+                    }
+                }
+                Content::Literal(value)
+            }
+            kind => {
+                let mut packets = Vec::new();
+                match self.read(1)? {
+                    0 => {
+                        let end = self.read(15)? as usize + self.at;
+                        while self.at < end {
+                            packets.push(self.packet()?);
+                        }
+                    }
+                    _ => {
+                        for _ in 0..self.read(11)? {
+                            packets.push(self.packet()?);
+                        }
+                    }
+                }
+                Content::Operator(kind, packets)
+            }
+        };
+        Ok(Packet { version, content })
+    }
+}
+
+#[cfg(test)]
+mod tests_day16 {
+    use super::*;
+    use crate::runner::Executor;
+
+    #[test]
+    fn test_run() -> Result<()> {
+        let (r1, r2) = Day16::run("inputs/day16.test")?;
+        assert_eq!(r1, 31);
+        assert_eq!(r2, 54);
+        Ok(())
+    }
+
+    #[test]
+    fn test_parse() -> Result<()> {
+        assert_eq!(
+            Day16::parse_input("D2FE28")?,
+            Packet {
+                version: 6,
+                content: Content::Literal(2021)
+            }
+        );
+        let packet = Day16::parse_input("38006F45291200")?;
+        assert_eq!(packet.value()?, 1);
+        assert!(Day16::parse_input("D2FE").is_err());
+        assert!(Day16::parse_input("XY").is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_version_sum() -> Result<()> {
+        for (hex, sum) in [
+            ("8A004A801A8002F478", 16),
+            ("620080001611562C8802118E34", 12),
+            ("C0015000016115A2E0802F182340", 23),
+            ("A0016C880162017C3686B18A3D4780", 31),
+        ] {
+            assert_eq!(Day16::parse_input(hex)?.version_sum(), sum);
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_value() -> Result<()> {
+        for (hex, value) in [
+            ("C200B40A82", 3),
+            ("04005AC33890", 54),
+            ("880086C3E88112", 7),
+            ("CE00C43D881120", 9),
+            ("D8005AC2A8F0", 1),
+            ("F600BC2D8F", 0),
+            ("9C005AC2F8F0", 0),
+            ("9C0141080250320F1802104A08", 1),
+        ] {
+            assert_eq!(Day16::parse_input(hex)?.value()?, value);
+        }
+        Ok(())
+    }
+
+    // Hex of a literal packet made of the given 4 bit groups
+    fn literal_hex(groups: &[u8]) -> String {
+        let mut bits = String::from("000100");
+        for (i, group) in groups.iter().enumerate() {
+            let more = if i + 1 < groups.len() { '1' } else { '0' };
+            bits.push_str(&format!("{}{:04b}", more, group));
+        }
+        while bits.len() % 4 != 0 {
+            bits.push('0');
+        }
+        (0..bits.len())
+            .step_by(4)
+            .map(|i| format!("{:X}", u8::from_str_radix(&bits[i..i + 4], 2).unwrap_or(0)))
+            .collect()
+    }
+
+    #[test]
+    fn test_overflow() -> Result<()> {
+        let max = Day16::parse_input(&literal_hex(&[15; 16]))?;
+        assert_eq!(max.value()?, u64::MAX);
+        // Leading zero groups don't count towards the width
+        assert_eq!(
+            Day16::parse_input(&literal_hex(&[0, 0, 1, 2]))?.value()?,
+            0x12
+        );
+        assert!(Day16::parse_input(&literal_hex(&[1; 17])).is_err());
+
+        let operator = |kind| Packet {
+            version: 0,
+            content: Content::Operator(kind, vec![max.clone(), max.clone()]),
+        };
+        assert!(operator(0).value().is_err());
+        assert!(operator(1).value().is_err());
+        assert_eq!(operator(3).value()?, u64::MAX);
+        Ok(())
+    }
+}
@@ -0,0 +1,107 @@
+use anyhow::{bail, Result};
+
+use crate::runner::{Parse, Run};
+
+pub struct Day17 {}
+
+impl Parse<Target> for Day17 {
+    fn parse_input(input: &str) -> Result<Target> {
+        let range = |r: &str| -> Result<(i32, i32)> {
+            match r.split_once("..") {
+                Some((a, b)) => Ok((a.parse()?, b.parse()?)),
+                None => bail!("invalid range {:?}", r),
+            }
+        };
+        let area = input.trim().strip_prefix("target area: ");
+        let (x, y) = match area.and_then(|a| a.split_once(", ")) {
+            Some((x, y)) => (x, y),
+            None => bail!("invalid target {:?}", input.trim()),
+        };
+        match (x.strip_prefix("x="), y.strip_prefix("y=")) {
+            (Some(x), Some(y)) => Target::new(range(x)?, range(y)?),
+            _ => bail!("invalid target {:?}", input.trim()),
+        }
+    }
+}
+
+impl Run<Target, i32> for Day17 {
+    fn part_one(input: &Target) -> Result<i32> {
+        input
+            .hits()
+            .into_iter()
+            .max()
+            .ok_or(anyhow::anyhow!("the target can't be hit"))
+    }
+
+    fn part_two(input: &Target) -> Result<i32> {
+        Ok(input.hits().len() as i32)
+    }
+}
+
+/// Target area ahead of the probe, as inclusive ranges
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Target {
+    x: (i32, i32),
+    y: (i32, i32),
+}
+
+impl Target {
+    pub fn new(x: (i32, i32), y: (i32, i32)) -> Result<Self> {
+        if x.0 > x.1 || y.0 > y.1 || x.0 <= 0 {
+            bail!("the target must be a non empty area ahead of the probe");
+        }
+        Ok(Self { x, y })
+    }
+
+    /// Highest point of the launch with this velocity, if it hits the target
+    pub fn launch(&self, (mut vx, mut vy): (i32, i32)) -> Option<i32> {
+        let (mut x, mut y, mut top) = (0, 0, 0);
+        while x <= self.x.1 && (vy > 0 || y >= self.y.0) {
+            x += vx;
+            y += vy;
+            vx -= vx.signum();
+            vy -= 1;
+            top = top.max(y);
+            if (self.x.0..=self.x.1).contains(&x) && (self.y.0..=self.y.1).contains(&y) {
+                return Some(top);
+            }
+        }
+        None
+    }
+
+    /// Highest point of every launch that hits the target
+    pub fn hits(&self) -> Vec<i32> {
+        // Going up the probe comes back to 0 with the same speed, so it can't
+        // be faster than the furthest edge of the target
+        let vy_max = self.y.0.abs().max(self.y.1.abs());
+        let vy_min = self.y.0.min(0);
+        (1..=self.x.1)
+            .flat_map(|vx| (vy_min..=vy_max).filter_map(move |vy| self.launch((vx, vy))))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests_day17 {
+    use super::*;
+    use crate::runner::Executor;
+
+    #[test]
+    fn test_run() -> Result<()> {
+        let (r1, r2) = Day17::run("inputs/day17.test")?;
+        assert_eq!(r1, 45);
+        assert_eq!(r2, 112);
+        Ok(())
+    }
+
+    #[test]
+    fn test_launch() -> Result<()> {
+        let target = Day17::parse_input("target area: x=20..30, y=-10..-5")?;
+        assert_eq!(target.launch((7, 2)), Some(3));
+        assert_eq!(target.launch((6, 3)), Some(6));
+        assert_eq!(target.launch((9, 0)), Some(0));
+        assert_eq!(target.launch((17, -4)), None);
+        assert!(Day17::parse_input("target area: x=-20..-30, y=-10..-5").is_err());
+        Ok(())
+    }
+}
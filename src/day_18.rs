@@ -0,0 +1,262 @@
+use std::fmt::Display;
+use std::str::FromStr;
+
+use anyhow::{bail, Error, Result};
+
+use crate::runner::{Parse, Run};
+
+pub struct Day18 {}
+
+impl Parse<Vec<Number>> for Day18 {
+    fn parse_input(input: &str) -> Result<Vec<Number>> {
+        input
+            .lines()
+            .map(str::trim)
+            .filter(|l| !l.is_empty())
+            .map(str::parse)
+            .collect()
+    }
+}
+
+impl Run<Vec<Number>, u64> for Day18 {
+    fn part_one(input: &Vec<Number>) -> Result<u64> {
+        let mut numbers = input.iter().cloned();
+        let first = numbers.next().ok_or(anyhow::anyhow!("no numbers"))?;
+        Ok(numbers.fold(first, |sum, n| sum.add(&n)).magnitude())
+    }
+
+    fn part_two(input: &Vec<Number>) -> Result<u64> {
+        (0..input.len())
+            .flat_map(|i| {
+                (0..input.len())
+                    .filter(move |j| i != *j)
+                    .map(move |j| (i, j))
+            })
+            .map(|(i, j)| input[i].add(&input[j]).magnitude())
+            .max()
+            .ok_or(anyhow::anyhow!("not enough numbers"))
+    }
+}
+
+/// Snailfish number kept as its regular numbers from left to right with how
+/// many pairs they are nested in
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Number(Vec<(u64, u8)>);
+
+impl Number {
+    /// Sum of both numbers, already reduced
+    pub fn add(&self, other: &Number) -> Number {
+        let mut sum = Number(
+            self.0
+                .iter()
+                .chain(other.0.iter())
+                .map(|(value, depth)| (*value, depth + 1))
+                .collect(),
+        );
+        sum.reduce();
+        sum
+    }
+
+    fn reduce(&mut self) {
+        while self.explode() || self.split() {}
+    }
+
+    // A pair nested in four pairs is made of two regular numbers next to each other
+    fn explode(&mut self) -> bool {
+        let i = match self.0.iter().position(|(_, depth)| *depth > 4) {
+            Some(i) => i,
+            None => return false,
+        };
+        let (left, depth) = self.0[i];
+        let (right, _) = self.0[i + 1];
+        if i > 0 {
+            self.0[i - 1].0 += left;
+        }
+        if let Some(next) = self.0.get_mut(i + 2) {
+            next.0 += right;
+        }
+        self.0[i] = (0, depth - 1);
+        self.0.remove(i + 1);
+        true
+    }
+
+    fn split(&mut self) -> bool {
+        let i = match self.0.iter().position(|(value, _)| *value >= 10) {
+            Some(i) => i,
+            None => return false,
+        };
+        let (value, depth) = self.0[i];
+        self.0[i] = (value / 2, depth + 1);
+        self.0.insert(i + 1, (value - value / 2, depth + 1));
+        true
+    }
+
+    pub fn magnitude(&self) -> u64 {
+        // Two regular numbers at the same depth next to each other are a pair
+        let mut stack: Vec<(u64, u8)> = Vec::new();
+        for (value, depth) in &self.0 {
+            let mut top = (*value, *depth);
+            while let Some((left, depth)) = stack.last().copied() {
+                if depth != top.1 || depth == 0 {
+                    break;
+                }
+                stack.pop();
+                top = (3 * left + 2 * top.0, depth - 1);
+            }
+            stack.push(top);
+        }
+        stack.first().map_or(0, |(value, _)| *value)
+    }
+}
+
+// Numbers in the input are reduced, so no pair is nested in more than four
+impl FromStr for Number {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let mut numbers = Vec::new();
+        let mut chars = s.trim().chars().peekable();
+        parse_element(&mut chars, 0, &mut numbers)
+            .map_err(|e| anyhow::anyhow!("invalid snailfish number {:?}: {}", s, e))?;
+        if numbers.len() < 2 {
+            bail!("invalid snailfish number {:?}: expected a pair", s);
+        }
+        if let Some(c) = chars.next() {
+            bail!(
+                "invalid snailfish number {:?}: unexpected {:?} after the pair",
+                s,
+                c
+            );
+        }
+        Ok(Number(numbers))
+    }
+}
+
+// A regular number, or a pair of two elements
+fn parse_element(
+    chars: &mut std::iter::Peekable<impl Iterator<Item = char>>,
+    depth: u8,
+    numbers: &mut Vec<(u64, u8)>,
+) -> Result<()> {
+    match chars.next() {
+        Some('[') if depth == 4 => bail!("pair nested in more than four pairs"),
+        Some('[') => {
+            parse_element(chars, depth + 1, numbers)?;
+            match chars.next() {
+                Some(',') => {}
+                c => bail!(
+                    "expected ',' between the elements of a pair, got {}",
+                    found(c)
+                ),
+            }
+            parse_element(chars, depth + 1, numbers)?;
+            match chars.next() {
+                Some(']') => Ok(()),
+                c => bail!("expected ']' after the second element, got {}", found(c)),
+            }
+        }
+        Some(c) if c.is_ascii_digit() => {
+            let mut value = c as u64 - '0' as u64;
+            while let Some(digit) = chars.peek().and_then(|c| c.to_digit(10)) {
+                chars.next();
+                value = value
+                    .checked_mul(10)
+                    .and_then(|v| v.checked_add(digit as u64))
+                    .ok_or(anyhow::anyhow!("regular number too big"))?;
+            }
+            numbers.push((value, depth));
+            Ok(())
+        }
+        c => bail!("expected a number or a pair, got {}", found(c)),
+    }
+}
+
+fn found(c: Option<char>) -> String {
+    c.map_or(String::from("the end"), |c| format!("{:?}", c))
+}
+
+// Rebuilds the pairs from the depths, like the magnitude
+impl Display for Number {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let mut stack: Vec<(String, u8)> = Vec::new();
+        for (value, depth) in &self.0 {
+            let mut top = (value.to_string(), *depth);
+            while let Some((_, depth)) = stack.last() {
+                if *depth != top.1 || *depth == 0 {
+                    break;
+                }
+                let (left, depth) = stack.pop().unwrap_or_default();
+                top = (format!("[{},{}]", left, top.0), depth - 1);
+            }
+            stack.push(top);
+        }
+        stack.iter().try_for_each(|(s, _)| write!(f, "{}", s))
+    }
+}
+
+#[cfg(test)]
+mod tests_day18 {
+    use super::*;
+    use crate::runner::Executor;
+
+    #[test]
+    fn test_run() -> Result<()> {
+        let (r1, r2) = Day18::run("inputs/day18.test")?;
+        assert_eq!(r1, 4140);
+        assert_eq!(r2, 3993);
+        Ok(())
+    }
+
+    #[test]
+    fn test_add() -> Result<()> {
+        let a: Number = "[[[[4,3],4],4],[7,[[8,4],9]]]".parse()?;
+        let b: Number = "[1,1]".parse()?;
+        assert_eq!(a.add(&b).to_string(), "[[[[0,7],4],[[7,8],[6,0]]],[8,1]]");
+        Ok(())
+    }
+
+    #[test]
+    fn test_magnitude() -> Result<()> {
+        let n: Number = "[[1,2],[[3,4],5]]".parse()?;
+        assert_eq!(n.magnitude(), 143);
+        let n: Number = "[[[[8,7],[7,7]],[[8,6],[7,7]]],[[[0,7],[6,6]],[8,7]]]".parse()?;
+        assert_eq!(n.magnitude(), 3488);
+        assert_eq!(
+            n.to_string(),
+            "[[[[8,7],[7,7]],[[8,6],[7,7]]],[[[0,7],[6,6]],[8,7]]]"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        let error = |input: &str| input.parse::<Number>().unwrap_err().to_string();
+        assert_eq!(
+            error("[1,2"),
+            "invalid snailfish number \"[1,2\": \
+             expected ']' after the second element, got the end"
+        );
+        assert!("[1,x]".parse::<Number>().is_err());
+        assert_eq!(
+            error("1"),
+            "invalid snailfish number \"1\": expected a pair"
+        );
+        assert_eq!(
+            error("[1,2][3,4]"),
+            "invalid snailfish number \"[1,2][3,4]\": unexpected '[' after the pair"
+        );
+        assert_eq!(
+            error("[1,2,3]"),
+            "invalid snailfish number \"[1,2,3]\": \
+             expected ']' after the second element, got ','"
+        );
+        assert_eq!(
+            error("[1]"),
+            "invalid snailfish number \"[1]\": \
+             expected ',' between the elements of a pair, got ']'"
+        );
+        assert!("[[1,2],]".parse::<Number>().is_err());
+        assert!("[[[[[1,2],3],4],5],6]".parse::<Number>().is_err());
+        assert!("[99999999999999999999,1]".parse::<Number>().is_err());
+    }
+}
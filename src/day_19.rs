@@ -0,0 +1,170 @@
+use std::collections::{HashMap, HashSet};
+
+use anyhow::{bail, Result};
+
+use crate::runner::{Parse, Run};
+
+pub struct Day19 {}
+
+pub type Point = [i32; 3];
+
+impl Parse<Vec<Vec<Point>>> for Day19 {
+    fn parse_input(input: &str) -> Result<Vec<Vec<Point>>> {
+        let mut scanners: Vec<Vec<Point>> = Vec::new();
+        for line in input.lines().map(str::trim).filter(|l| !l.is_empty()) {
+            if line.starts_with("---") {
+                scanners.push(Vec::new());
+                continue;
+            }
+            let coordinates: Vec<i32> =
+                line.split(',').map(str::parse).collect::<Result<_, _>>()?;
+            match (scanners.last_mut(), coordinates.as_slice()) {
+                (Some(beacons), [x, y, z]) => beacons.push([*x, *y, *z]),
+                _ => bail!("invalid beacon {:?}", line),
+            }
+        }
+        Ok(scanners)
+    }
+}
+
+impl Run<Vec<Vec<Point>>, i32> for Day19 {
+    fn part_one(input: &Vec<Vec<Point>>) -> Result<i32> {
+        Ok(locate(input)?.beacons.len() as i32)
+    }
+
+    fn part_two(input: &Vec<Vec<Point>>) -> Result<i32> {
+        let map = locate(input)?;
+        let distance = |a: &Point, b: &Point| (0..3).map(|i| (a[i] - b[i]).abs()).sum::<i32>();
+        Ok(map
+            .scanners
+            .iter()
+            .flat_map(|a| map.scanners.iter().map(move |b| distance(a, b)))
+            .max()
+            .unwrap_or(0))
+    }
+}
+
+/// Scanners and beacons relative to the first scanner
+#[derive(Debug, Clone)]
+pub struct Map {
+    pub scanners: Vec<Point>,
+    pub beacons: HashSet<Point>,
+}
+
+// Axis taken for each coordinate and its sign
+type Rotation = ([usize; 3], [i32; 3]);
+
+/// The 24 ways a scanner can be facing, the permutation parity and the
+/// signs must keep the orientation
+fn rotations() -> Vec<Rotation> {
+    let permutations = [
+        ([0, 1, 2], 1),
+        ([1, 2, 0], 1),
+        ([2, 0, 1], 1),
+        ([0, 2, 1], -1),
+        ([1, 0, 2], -1),
+        ([2, 1, 0], -1),
+    ];
+    let mut rotations = Vec::new();
+    for (axes, parity) in permutations {
+        for signs in 0..8 {
+            let signs = [0, 1, 2].map(|i| if signs >> i & 1 == 1 { -1 } else { 1 });
+            if parity * signs.iter().product::<i32>() == 1 {
+                rotations.push((axes, signs));
+            }
+        }
+    }
+    rotations
+}
+
+fn rotate((axes, signs): &Rotation, p: &Point) -> Point {
+    [0, 1, 2].map(|i| signs[i] * p[axes[i]])
+}
+
+/// Places every scanner by finding at least 12 beacons it shares with the
+/// ones already placed
+pub fn locate(scanners: &[Vec<Point>]) -> Result<Map> {
+    let rotations = rotations();
+    let mut map = Map {
+        scanners: vec![[0, 0, 0]],
+        beacons: scanners.first().into_iter().flatten().copied().collect(),
+    };
+    let mut pending: Vec<&Vec<Point>> = scanners.iter().skip(1).collect();
+    while !pending.is_empty() {
+        let before = pending.len();
+        pending.retain(|beacons| match align(&map.beacons, beacons, &rotations) {
+            Some((position, placed)) => {
+                map.scanners.push(position);
+                map.beacons.extend(placed);
+                false
+            }
+            None => true,
+        });
+        if pending.len() == before {
+            bail!("{} scanners don't overlap the others", pending.len());
+        }
+    }
+    Ok(map)
+}
+
+// Position of the scanner and its beacons once placed, if it can be
+fn align(
+    known: &HashSet<Point>,
+    beacons: &[Point],
+    rotations: &[Rotation],
+) -> Option<(Point, Vec<Point>)> {
+    for rotation in rotations {
+        let rotated: Vec<Point> = beacons.iter().map(|b| rotate(rotation, b)).collect();
+        let mut offsets: HashMap<Point, usize> = HashMap::new();
+        for k in known {
+            for b in &rotated {
+                let offset = [0, 1, 2].map(|i| k[i] - b[i]);
+                let count = offsets.entry(offset).or_default();
+                *count += 1;
+                if *count >= 12 {
+                    let placed = rotated
+                        .iter()
+                        .map(|b| [0, 1, 2].map(|i| b[i] + offset[i]))
+                        .collect();
+                    return Some((offset, placed));
+                }
+            }
+        }
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests_day19 {
+    use super::*;
+    use crate::runner::Executor;
+
+    #[test]
+    fn test_run() -> Result<()> {
+        let (r1, r2) = Day19::run("inputs/day19.test")?;
+        assert_eq!(r1, 79);
+        assert_eq!(r2, 3621);
+        Ok(())
+    }
+
+    #[test]
+    fn test_rotations() {
+        let rotations = rotations();
+        assert_eq!(rotations.len(), 24);
+        let p = [1, 2, 3];
+        let facings: HashSet<Point> = rotations.iter().map(|r| rotate(r, &p)).collect();
+        assert_eq!(facings.len(), 24);
+        assert!(facings.contains(&[-1, -2, 3]));
+        assert!(!facings.contains(&[-1, 2, 3]));
+    }
+
+    #[test]
+    fn test_locate() -> Result<()> {
+        let scanners = Day19::parse_input(include_str!("../inputs/day19.test"))?;
+        let map = locate(&scanners)?;
+        assert!(map.scanners.contains(&[68, -1246, -43]));
+        assert!(map.scanners.contains(&[1105, -1205, 1229]));
+        assert!(locate(&[scanners[0].clone(), vec![[1, 2, 3]]]).is_err());
+        Ok(())
+    }
+}
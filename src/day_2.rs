@@ -1,63 +1,419 @@
-use anyhow::Result;
+use std::fmt::Display;
+use std::io::BufRead;
 
-use crate::runner::{Parse, Run};
+use anyhow::{bail, Error, Result};
+
+use crate::runner::{Parse, Run, RunStream};
 
 pub struct Day2 {}
 
 impl Parse<Vec<Movements>> for Day2 {
     fn parse_input(input: &str) -> Result<Vec<Movements>> {
-        let r = input
-            .lines()
-            .filter_map(|s| match s.split_once(' ') {
-                Some(("forward", x)) => Some(Movements::Forward(x.parse().ok()?)),
-                Some(("down", x)) => Some(Movements::Down(x.parse().ok()?)),
-                Some(("up", x)) => Some(Movements::Up(x.parse().ok()?)),
-                _ => None,
+        parse_course(input)
+    }
+}
+
+impl Run<Vec<Movements>, i64> for Day2 {
+    fn part_one(input: &Vec<Movements>) -> Result<i64> {
+        let last = Submarine::new(Simple).run(input).last();
+        Ok(last.unwrap_or_default().product())
+    }
+
+    fn part_two(input: &Vec<Movements>) -> Result<i64> {
+        let last = Submarine::new(Aim).run(input).last();
+        Ok(last.unwrap_or_default().product())
+    }
+}
+
+impl RunStream<i64> for Day2 {
+    fn run_lines(input: impl BufRead) -> Result<(i64, i64)> {
+        let mut simple = Submarine::new(Simple);
+        let mut aim = Submarine::new(Aim);
+        // Only the top level movement being parsed is kept, so a repeat block
+        // stays in memory until its closing brace
+        let lines = input.lines().map(|line| line.map_err(Error::from));
+        for movement in movements(tokenize(lines)) {
+            let movement = movement?;
+            simple.step(&movement);
+            aim.step(&movement);
+        }
+        Ok((simple.state().product(), aim.state().product()))
+    }
+}
+
+/// Where the submarine is, depth grows downwards and can go above the surface
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct State {
+    pub horizontal: i64,
+    pub depth: i64,
+    pub aim: i64,
+}
+
+impl State {
+    pub fn product(&self) -> i64 {
+        self.horizontal * self.depth
+    }
+}
+
+/// How a movement changes the state of the submarine
+pub trait Steering {
+    fn apply(&self, state: &mut State, movement: &Movements);
+}
+
+/// Down and up change the depth directly
+pub struct Simple;
+
+/// Down and up change the aim, and forward goes down by the aim
+pub struct Aim;
+
+impl Steering for Simple {
+    fn apply(&self, state: &mut State, movement: &Movements) {
+        match movement {
+            Movements::Forward(x) => state.horizontal += *x as i64,
+            Movements::Back(x) => state.horizontal -= *x as i64,
+            Movements::Down(x) => state.depth += *x as i64,
+            Movements::Up(x) => state.depth -= *x as i64,
+            Movements::Hold(_) | Movements::Repeat(..) => {}
+        }
+    }
+}
+
+impl Steering for Aim {
+    fn apply(&self, state: &mut State, movement: &Movements) {
+        match movement {
+            Movements::Forward(x) => {
+                state.horizontal += *x as i64;
+                state.depth += state.aim * *x as i64;
+            }
+            Movements::Back(x) => {
+                state.horizontal -= *x as i64;
+                state.depth -= state.aim * *x as i64;
+            }
+            Movements::Down(x) => state.aim += *x as i64,
+            Movements::Up(x) => state.aim -= *x as i64,
+            Movements::Hold(_) | Movements::Repeat(..) => {}
+        }
+    }
+}
+
+/// Runs the movements one at a time with the given steering
+pub struct Submarine<S: Steering> {
+    steering: S,
+    state: State,
+}
+
+impl<S: Steering> Submarine<S> {
+    pub fn new(steering: S) -> Self {
+        Self {
+            steering,
+            state: State::default(),
+        }
+    }
+
+    pub fn state(&self) -> State {
+        self.state
+    }
+
+    /// Applies the movement, returning the new state
+    pub fn step(&mut self, movement: &Movements) -> State {
+        match movement {
+            Movements::Repeat(times, block) => {
+                for _ in 0..*times {
+                    block.iter().for_each(|m| {
+                        self.step(m);
+                    });
+                }
+            }
+            _ => self.steering.apply(&mut self.state, movement),
+        }
+        self.state
+    }
+
+    /// Applies all the movements, giving the state after each one as it goes,
+    /// repeat blocks are unrolled so every repeated movement has its state
+    pub fn run<'a>(&'a mut self, movements: &'a [Movements]) -> impl Iterator<Item = State> + 'a {
+        unroll(movements).map(|m| self.step(m))
+    }
+}
+
+// A single step in place, what `hold N` is unrolled into
+static HOLD: [Movements; 1] = [Movements::Hold(1)];
+
+/// Walks the movements with the repeat blocks expanded and `hold N` split
+/// into N steps, lazily so large repeat counts take no memory
+pub fn unroll(movements: &[Movements]) -> impl Iterator<Item = &Movements> {
+    // Each block with the movements left in this pass and the passes after it
+    let mut blocks = vec![(movements, movements.iter(), 0)];
+    std::iter::from_fn(move || loop {
+        let (block, left, passes) = blocks.last_mut()?;
+        match left.next() {
+            Some(Movements::Repeat(times, inner)) if *times > 0 && !inner.is_empty() => {
+                blocks.push((inner, inner.iter(), times - 1))
+            }
+            Some(Movements::Hold(n)) if *n > 1 => blocks.push((&HOLD, HOLD.iter(), n - 1)),
+            Some(Movements::Repeat(..) | Movements::Hold(0)) => {}
+            Some(movement) => return Some(movement),
+            None if *passes > 0 => {
+                *passes -= 1;
+                *left = block.iter();
+            }
+            None => {
+                blocks.pop();
+            }
+        }
+    })
+}
+
+/// Most steps kept by `course_path`, past that the course is too long to list
+pub const MAX_PATH_STEPS: usize = 1_000_000;
+
+/// State of the submarine with both steerings after a movement
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Step {
+    pub index: usize,
+    pub movement: Movements,
+    pub simple: State,
+    pub aim: State,
+}
+
+/// Every step of the course with both steerings, numbered from 1
+pub fn course_path(movements: &[Movements]) -> Result<Vec<Step>> {
+    let mut simple = Submarine::new(Simple);
+    let mut aim = Submarine::new(Aim);
+    unroll(movements)
+        .enumerate()
+        .map(|(i, m)| match i < MAX_PATH_STEPS {
+            true => Ok(Step {
+                index: i + 1,
+                movement: m.clone(),
+                simple: simple.step(m),
+                aim: aim.step(m),
+            }),
+            false => bail!("the course has more than {} steps", MAX_PATH_STEPS),
+        })
+        .collect()
+}
+
+pub fn path_to_csv(path: &[Step]) -> String {
+    let mut csv =
+        String::from("step,command,simple_horizontal,simple_depth,aim_horizontal,aim_depth,aim\n");
+    for step in path {
+        csv.push_str(&format!(
+            "{},{},{},{},{},{},{}\n",
+            step.index,
+            step.movement,
+            step.simple.horizontal,
+            step.simple.depth,
+            step.aim.horizontal,
+            step.aim.depth,
+            step.aim.aim
+        ));
+    }
+    csv
+}
+
+pub fn path_to_json(path: &[Step]) -> String {
+    let state = |s: &State| {
+        format!(
+            "{{\"horizontal\":{},\"depth\":{},\"aim\":{}}}",
+            s.horizontal, s.depth, s.aim
+        )
+    };
+    let steps: Vec<String> = path
+        .iter()
+        .map(|step| {
+            format!(
+                "  {{\"step\":{},\"command\":\"{}\",\"simple\":{},\"aim\":{}}}",
+                step.index,
+                step.movement,
+                state(&step.simple),
+                state(&step.aim)
+            )
+        })
+        .collect();
+    format!("[\n{}\n]\n", steps.join(",\n"))
+}
+
+/// Plots the depth along the course, one column per group of steps and the
+/// surface drawn with `~`, deeper rows are further down
+pub fn depth_profile(states: &[State], width: usize, height: usize) -> String {
+    let (min, max) = match (
+        states.iter().map(|s| s.depth).min(),
+        states.iter().map(|s| s.depth).max(),
+    ) {
+        (Some(min), Some(max)) => (min.min(0), max.max(0)),
+        _ => return String::new(),
+    };
+    let height = height.max(2);
+    let chunk = states.len().div_ceil(width.max(1));
+    let row_of = |depth: i64| match max - min {
+        0 => 0,
+        range => ((depth - min) as i128 * (height - 1) as i128 / range as i128) as usize,
+    };
+    let columns: Vec<usize> = states
+        .chunks(chunk)
+        .map(|c| row_of(c.iter().map(|s| s.depth).max().unwrap_or(min)))
+        .collect();
+    let label = min.to_string().len().max(max.to_string().len());
+    let mut plot = String::new();
+    for row in 0..height {
+        let depth = min + (max - min) * row as i64 / (height - 1) as i64;
+        let line: String = columns
+            .iter()
+            .map(|c| match (*c == row, row == row_of(0)) {
+                (true, _) => '#',
+                (false, true) => '~',
+                (false, false) => ' ',
             })
             .collect();
-        Ok(r)
+        plot.push_str(format!("{:>label$} |{}", depth, line).trim_end());
+        plot.push('\n');
     }
+    plot
 }
 
-impl Run<Vec<Movements>, u32> for Day2 {
-    fn part_one(input: &Vec<Movements>) -> Result<u32> {
-        let mut x_pos = 0;
-        let mut y_pos = 0;
-        input.iter().for_each(|movement| match movement {
-            Movements::Forward(x) => x_pos += x,
-            Movements::Down(x) => y_pos += x,
-            Movements::Up(x) => y_pos -= x,
-        });
-        Ok(x_pos * y_pos)
+/// Parses a course, commands are `<name> <amount>` separated by whitespace
+/// and `repeat <times> { ... }` runs a block several times, blocks can nest
+pub fn parse_course(input: &str) -> Result<Vec<Movements>> {
+    movements(tokenize(input.lines().map(|line| Ok(line.to_string())))).collect()
+}
+
+/// A word of the course with the line it comes from, braces are words on their own
+struct Token {
+    text: String,
+    line: usize,
+}
+
+fn tokenize(lines: impl Iterator<Item = Result<String>>) -> impl Iterator<Item = Result<Token>> {
+    lines.enumerate().flat_map(|(i, line)| match line {
+        Ok(line) => split_words(&line, i + 1).into_iter().map(Ok).collect(),
+        Err(e) => vec![Err(e)],
+    })
+}
+
+fn split_words(line: &str, number: usize) -> Vec<Token> {
+    let mut tokens = Vec::new();
+    let mut push = |text: &str| {
+        tokens.push(Token {
+            text: text.to_string(),
+            line: number,
+        })
+    };
+    for word in line.split_whitespace() {
+        let mut rest = word;
+        while let Some(at) = rest.find(['{', '}']) {
+            if at > 0 {
+                push(&rest[..at]);
+            }
+            push(&rest[at..at + 1]);
+            rest = &rest[at + 1..];
+        }
+        if !rest.is_empty() {
+            push(rest);
+        }
     }
+    tokens
+}
 
-    fn part_two(input: &Vec<Movements>) -> Result<u32> {
-        let mut x_pos = 0;
-        let mut y_pos = 0;
-        let mut aim = 0;
-        input.iter().for_each(|movement| match movement {
-            Movements::Forward(x) => {
-                x_pos += x;
-                y_pos += aim * x;
+// Top level movements, each one is given as soon as its last token is read
+fn movements(
+    mut tokens: impl Iterator<Item = Result<Token>>,
+) -> impl Iterator<Item = Result<Movements>> {
+    std::iter::from_fn(move || {
+        let token = tokens.next()?;
+        Some(token.and_then(|token| parse_movement(&mut tokens, token)))
+    })
+}
+
+// Parses until the closing brace of the block opened at line `opened`
+fn parse_block(
+    tokens: &mut impl Iterator<Item = Result<Token>>,
+    opened: usize,
+) -> Result<Vec<Movements>> {
+    let mut block = Vec::new();
+    loop {
+        match tokens.next().transpose()? {
+            None => bail!("line {}: repeat block is never closed", opened),
+            Some(token) if token.text == "}" => return Ok(block),
+            Some(token) => block.push(parse_movement(tokens, token)?),
+        }
+    }
+}
+
+fn parse_movement(
+    tokens: &mut impl Iterator<Item = Result<Token>>,
+    token: Token,
+) -> Result<Movements> {
+    let movement = match token.text.as_str() {
+        "}" => bail!("line {}: unexpected '}}'", token.line),
+        "repeat" => {
+            let times = parse_amount(tokens, &token)?;
+            match tokens.next().transpose()? {
+                Some(brace) if brace.text == "{" => {}
+                _ => bail!("line {}: expected '{{' after repeat", token.line),
             }
-            Movements::Down(x) => aim += x,
-            Movements::Up(x) => aim -= x,
-        });
-        Ok(x_pos * y_pos)
+            Movements::Repeat(times, parse_block(tokens, token.line)?)
+        }
+        "forward" => Movements::Forward(parse_amount(tokens, &token)?),
+        "back" => Movements::Back(parse_amount(tokens, &token)?),
+        "down" => Movements::Down(parse_amount(tokens, &token)?),
+        "up" => Movements::Up(parse_amount(tokens, &token)?),
+        "hold" => Movements::Hold(parse_amount(tokens, &token)?),
+        text => bail!("line {}: unknown command {:?}", token.line, text),
+    };
+    Ok(movement)
+}
+
+fn parse_amount(tokens: &mut impl Iterator<Item = Result<Token>>, command: &Token) -> Result<u32> {
+    match tokens.next().transpose()? {
+        Some(token) => match token.text.parse() {
+            Ok(amount) => Ok(amount),
+            Err(_) => bail!(
+                "line {}: invalid amount {:?} for {}",
+                token.line,
+                token.text,
+                command.text
+            ),
+        },
+        None => bail!("line {}: missing amount for {}", command.line, command.text),
     }
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub enum Movements {
     Forward(u32),
+    /// Goes backwards, undoing a forward of the same amount
+    Back(u32),
     Down(u32),
     Up(u32),
+    /// Stays in place for the given number of steps
+    Hold(u32),
+    /// Runs the block the given number of times
+    Repeat(u32, Vec<Movements>),
+}
+
+// Written back the way the course is parsed
+impl Display for Movements {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Movements::Forward(x) => write!(f, "forward {}", x),
+            Movements::Back(x) => write!(f, "back {}", x),
+            Movements::Down(x) => write!(f, "down {}", x),
+            Movements::Up(x) => write!(f, "up {}", x),
+            Movements::Hold(x) => write!(f, "hold {}", x),
+            Movements::Repeat(times, block) => {
+                write!(f, "repeat {} {{", times)?;
+                block.iter().try_for_each(|m| write!(f, " {}", m))?;
+                write!(f, " }}")
+            }
+        }
+    }
 }
 
 #[cfg(test)]
 mod tests_day2 {
     use super::*;
-    use crate::runner::Executor;
+    use crate::runner::{Executor, StreamExecutor};
     use anyhow::Result;
 
     #[test]
@@ -68,6 +424,14 @@ mod tests_day2 {
         Ok(())
     }
 
+    #[test]
+    fn test_run_stream() -> Result<()> {
+        let (r1, r2) = Day2::run_stream("inputs/day2.test")?;
+        assert_eq!(r1, 150);
+        assert_eq!(r2, 900);
+        Ok(())
+    }
+
     #[test]
     fn test_part_one() -> Result<()> {
         let movements = vec![
@@ -97,4 +461,179 @@ mod tests_day2 {
         assert_eq!(pos, 900);
         Ok(())
     }
+
+    #[test]
+    fn test_trajectory() {
+        let movements = vec![
+            Movements::Forward(5),
+            Movements::Down(5),
+            Movements::Forward(8),
+        ];
+        let trajectory: Vec<State> = Submarine::new(Aim).run(&movements).collect();
+        assert_eq!(
+            trajectory,
+            vec![
+                State {
+                    horizontal: 5,
+                    depth: 0,
+                    aim: 0
+                },
+                State {
+                    horizontal: 5,
+                    depth: 0,
+                    aim: 5
+                },
+                State {
+                    horizontal: 13,
+                    depth: 40,
+                    aim: 5
+                },
+            ]
+        );
+        let mut submarine = Submarine::new(Simple);
+        assert_eq!(submarine.step(&Movements::Down(3)).depth, 3);
+        assert_eq!(submarine.state().depth, 3);
+    }
+
+    #[test]
+    fn test_negative_depth() -> Result<()> {
+        let movements = vec![
+            Movements::Forward(2),
+            Movements::Up(3),
+            Movements::Forward(4),
+        ];
+        assert_eq!(Day2::part_one(&movements)?, -18);
+        assert_eq!(Day2::part_two(&movements)?, -72);
+        Ok(())
+    }
+
+    #[test]
+    fn test_parse_course() -> Result<()> {
+        let course =
+            parse_course("forward 5\nrepeat 2 {\n  down 1\n  repeat 3 {back 2}\n}\nhold 4\n")?;
+        assert_eq!(
+            course,
+            vec![
+                Movements::Forward(5),
+                Movements::Repeat(
+                    2,
+                    vec![
+                        Movements::Down(1),
+                        Movements::Repeat(3, vec![Movements::Back(2)])
+                    ]
+                ),
+                Movements::Hold(4),
+            ]
+        );
+        assert_eq!(parse_course("repeat 2 { up 1 }")?.len(), 1);
+        Ok(())
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        let error = |input: &str| parse_course(input).unwrap_err().to_string();
+        assert_eq!(
+            error("forward 1\nsideways 2"),
+            "line 2: unknown command \"sideways\""
+        );
+        assert_eq!(error("down x"), "line 1: invalid amount \"x\" for down");
+        assert_eq!(error("up"), "line 1: missing amount for up");
+        assert_eq!(
+            error("repeat 2 {\nforward 1"),
+            "line 1: repeat block is never closed"
+        );
+        assert_eq!(error("forward 1 }"), "line 1: unexpected '}'");
+        assert_eq!(
+            error("repeat 2 forward 1"),
+            "line 1: expected '{' after repeat"
+        );
+    }
+
+    #[test]
+    fn test_extended_commands() -> Result<()> {
+        let course = parse_course("down 2\nrepeat 3 { forward 2 hold 1 }\nback 1")?;
+        assert_eq!(Day2::part_one(&course)?, 5 * 2);
+        // Aim 2: forward 6 goes down 12, back 1 comes up 2
+        assert_eq!(Day2::part_two(&course)?, 5 * 10);
+        let trajectory: Vec<State> = Submarine::new(Simple).run(&course).collect();
+        assert_eq!(trajectory.len(), 8);
+        assert_eq!(trajectory[1], trajectory[2]);
+        Ok(())
+    }
+
+    #[test]
+    fn test_course_path() -> Result<()> {
+        let course = parse_course("forward 5\nrepeat 2 { down 5 }\nback 2")?;
+        assert_eq!(course[1].to_string(), "repeat 2 { down 5 }");
+        let path = course_path(&course)?;
+        assert_eq!(path.len(), 4);
+        assert_eq!(path[3].movement, Movements::Back(2));
+        assert_eq!(
+            path_to_csv(&path),
+            "step,command,simple_horizontal,simple_depth,aim_horizontal,aim_depth,aim
+1,forward 5,5,0,5,0,0
+2,down 5,5,5,5,0,5
+3,down 5,5,10,5,0,10
+4,back 2,3,10,3,-20,10
+"
+        );
+        assert_eq!(
+            path_to_json(&path[..1]),
+            "[\n  {\"step\":1,\"command\":\"forward 5\",\
+             \"simple\":{\"horizontal\":5,\"depth\":0,\"aim\":0},\
+             \"aim\":{\"horizontal\":5,\"depth\":0,\"aim\":0}}\n]\n"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_multi_line_stream() -> Result<()> {
+        let course = "forward 3\nrepeat 2 {\n  down 2\n  repeat 2 {\n    forward 1 }\n}\nup 1\n";
+        let movements = Day2::parse_input(course)?;
+        let streamed = Day2::run_lines(course.as_bytes())?;
+        assert_eq!(
+            streamed,
+            (Day2::part_one(&movements)?, Day2::part_two(&movements)?)
+        );
+        assert_eq!(streamed, (7 * 3, 7 * 12));
+        assert!(Day2::run_lines("repeat 2 {\nforward 1\n".as_bytes()).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_hold() -> Result<()> {
+        let course = parse_course("forward 2\nhold 4\nhold 0\ndown 1")?;
+        let path = course_path(&course)?;
+        assert_eq!(path.len(), 6);
+        assert!(path[1..5]
+            .iter()
+            .all(|step| step.movement == Movements::Hold(1)
+                && step.simple == path[0].simple
+                && step.aim == path[0].aim));
+        Ok(())
+    }
+
+    #[test]
+    fn test_large_repeat() -> Result<()> {
+        let course = parse_course("repeat 4294967295 { forward 1 repeat 4294967295 { up 1 } }")?;
+        let steps: Vec<State> = Submarine::new(Simple).run(&course).take(3).collect();
+        assert_eq!(steps[2].depth, -2);
+        assert_eq!(
+            course_path(&course).unwrap_err().to_string(),
+            "the course has more than 1000000 steps"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_depth_profile() -> Result<()> {
+        let course = parse_course("down 4 up 2 up 4 down 2")?;
+        let states: Vec<State> = course_path(&course)?.iter().map(|s| s.simple).collect();
+        assert_eq!(
+            depth_profile(&states, 4, 4),
+            "-2 |  #\n 0 |~~~#\n 2 | #\n 4 |#\n"
+        );
+        assert_eq!(depth_profile(&[], 4, 4), "");
+        Ok(())
+    }
 }
@@ -0,0 +1,124 @@
+use anyhow::{bail, Result};
+
+use crate::grid::Grid;
+use crate::runner::{Parse, Run};
+
+pub struct Day20 {}
+
+impl Parse<Scan> for Day20 {
+    fn parse_input(input: &str) -> Result<Scan> {
+        let (algorithm, image) = match input.trim().split_once("\n\n") {
+            Some(parts) => parts,
+            None => bail!("expected the algorithm, a blank line and the image"),
+        };
+        let algorithm: Vec<bool> = algorithm.trim().chars().map(|c| c == '#').collect();
+        if algorithm.len() != 512 {
+            bail!("the algorithm has {} pixels, expected 512", algorithm.len());
+        }
+        let image = Image {
+            pixels: Grid::parse_chars(image)?.map(|c| *c == '#'),
+            background: false,
+        };
+        Ok(Scan { algorithm, image })
+    }
+}
+
+impl Run<Scan, usize> for Day20 {
+    fn part_one(input: &Scan) -> Result<usize> {
+        input.enhance(2)?.lit()
+    }
+
+    fn part_two(input: &Scan) -> Result<usize> {
+        input.enhance(50)?.lit()
+    }
+}
+
+/// Image enhancement algorithm with the input image
+#[derive(Debug, Clone)]
+pub struct Scan {
+    algorithm: Vec<bool>,
+    image: Image,
+}
+
+/// Pixels of the image, the infinite rest of it is all `background`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Image {
+    pixels: Grid<bool>,
+    background: bool,
+}
+
+impl Image {
+    fn pixel(&self, row: isize, column: isize) -> bool {
+        match (usize::try_from(row), usize::try_from(column)) {
+            (Ok(row), Ok(column)) => *self.pixels.get((row, column)).unwrap_or(&self.background),
+            _ => self.background,
+        }
+    }
+
+    /// Number of lit pixels, fails when the infinite background is lit
+    pub fn lit(&self) -> Result<usize> {
+        if self.background {
+            bail!("infinitely many pixels are lit");
+        }
+        Ok(self.pixels.iter().filter(|(_, lit)| **lit).count())
+    }
+}
+
+impl Scan {
+    /// Applies the algorithm `times`, the image grows a pixel on every side each time
+    pub fn enhance(&self, times: usize) -> Result<Image> {
+        let mut image = self.image.clone();
+        for _ in 0..times {
+            let (width, height) = (image.pixels.width() + 2, image.pixels.height() + 2);
+            let rows = (0..height as isize)
+                .map(|row| {
+                    (0..width as isize)
+                        .map(|column| {
+                            let index = (-1..=1).flat_map(|i| (-1..=1).map(move |j| (i, j))).fold(
+                                0,
+                                |index, (i, j)| {
+                                    index << 1 | image.pixel(row - 1 + i, column - 1 + j) as usize
+                                },
+                            );
+                            self.algorithm[index]
+                        })
+                        .collect()
+                })
+                .collect();
+            let background = self.algorithm[if image.background { 511 } else { 0 }];
+            image = Image {
+                pixels: Grid::from_rows(rows)?,
+                background,
+            };
+        }
+        Ok(image)
+    }
+}
+
+#[cfg(test)]
+mod tests_day20 {
+    use super::*;
+    use crate::runner::Executor;
+
+    #[test]
+    fn test_run() -> Result<()> {
+        let (r1, r2) = Day20::run("inputs/day20.test")?;
+        assert_eq!(r1, 35);
+        assert_eq!(r2, 3351);
+        Ok(())
+    }
+
+    #[test]
+    fn test_background() -> Result<()> {
+        // The first pixel of the algorithm is lit, so the background flashes
+        let scan = Day20::parse_input(include_str!("../inputs/day20_flashing.test"))?;
+        let image = scan.enhance(1)?;
+        assert!(image.background);
+        assert!(image.lit().is_err());
+        assert_eq!(image.pixels.width(), 10);
+        assert_eq!(image.pixels.iter().filter(|(_, lit)| **lit).count(), 58);
+        assert!(!scan.enhance(2)?.background);
+        assert!(Day20::parse_input("#.#\n\n#.\n").is_err());
+        Ok(())
+    }
+}
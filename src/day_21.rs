@@ -0,0 +1,108 @@
+use std::collections::HashMap;
+
+use anyhow::{bail, Result};
+
+use crate::runner::{Parse, Run};
+
+pub struct Day21 {}
+
+impl Parse<[u64; 2]> for Day21 {
+    fn parse_input(input: &str) -> Result<[u64; 2]> {
+        let positions: Vec<u64> = input
+            .lines()
+            .filter(|l| !l.trim().is_empty())
+            .map(|l| match l.rsplit_once(": ") {
+                Some((_, p)) => Ok(p.trim().parse()?),
+                None => bail!("invalid player {:?}", l),
+            })
+            .collect::<Result<_>>()?;
+        match positions.as_slice() {
+            [a, b] if (1..=10).contains(a) && (1..=10).contains(b) => Ok([*a, *b]),
+            _ => bail!("expected two players on spaces 1 to 10"),
+        }
+    }
+}
+
+impl Run<[u64; 2], u64> for Day21 {
+    fn part_one(input: &[u64; 2]) -> Result<u64> {
+        Ok(deterministic_game(*input))
+    }
+
+    fn part_two(input: &[u64; 2]) -> Result<u64> {
+        let [a, b] = dirac_wins(input[0], input[1], 0, 0, &mut HashMap::new());
+        Ok(a.max(b))
+    }
+}
+
+/// Losing score times the rolls of a game to 1000 with a die that rolls 1 to 100
+pub fn deterministic_game(start: [u64; 2]) -> u64 {
+    let (mut positions, mut scores) = (start, [0, 0]);
+    let mut rolls = 0;
+    let mut player = 0;
+    loop {
+        let moves: u64 = (0..3).map(|i| (rolls + i) % 100 + 1).sum();
+        rolls += 3;
+        positions[player] = (positions[player] + moves - 1) % 10 + 1;
+        scores[player] += positions[player];
+        if scores[player] >= 1000 {
+            return scores[1 - player] * rolls;
+        }
+        player = 1 - player;
+    }
+}
+
+// How many universes move each sum of three Dirac rolls
+const DIRAC_ROLLS: [(u64, u64); 7] = [(3, 1), (4, 3), (5, 6), (6, 7), (7, 6), (8, 3), (9, 1)];
+
+/// Universes where each player wins a game to 21, the player to move first
+pub fn dirac_wins(
+    position: u64,
+    other: u64,
+    score: u64,
+    other_score: u64,
+    known: &mut HashMap<[u64; 4], [u64; 2]>,
+) -> [u64; 2] {
+    let key = [position, other, score, other_score];
+    if let Some(wins) = known.get(&key) {
+        return *wins;
+    }
+    let mut wins = [0, 0];
+    for (moves, universes) in DIRAC_ROLLS {
+        let next = (position + moves - 1) % 10 + 1;
+        if score + next >= 21 {
+            wins[0] += universes;
+            continue;
+        }
+        // The other player moves next, so the result comes swapped
+        let [theirs, mine] = dirac_wins(other, next, other_score, score + next, known);
+        wins[0] += mine * universes;
+        wins[1] += theirs * universes;
+    }
+    known.insert(key, wins);
+    wins
+}
+
+#[cfg(test)]
+mod tests_day21 {
+    use super::*;
+    use crate::runner::Executor;
+
+    #[test]
+    fn test_run() -> Result<()> {
+        let (r1, r2) = Day21::run("inputs/day21.test")?;
+        assert_eq!(r1, 739785);
+        assert_eq!(r2, 444356092776315);
+        Ok(())
+    }
+
+    #[test]
+    fn test_dirac_wins() -> Result<()> {
+        let wins = dirac_wins(4, 8, 0, 0, &mut HashMap::new());
+        assert_eq!(wins, [444356092776315, 341960390180808]);
+        assert!(Day21::parse_input(
+            "Player 1 starting position: 11\nPlayer 2 starting position: 8"
+        )
+        .is_err());
+        Ok(())
+    }
+}
@@ -0,0 +1,145 @@
+use anyhow::{bail, Result};
+
+use crate::runner::{Parse, Run};
+
+pub struct Day22 {}
+
+impl Parse<Vec<Step>> for Day22 {
+    fn parse_input(input: &str) -> Result<Vec<Step>> {
+        input
+            .lines()
+            .map(str::trim)
+            .filter(|l| !l.is_empty())
+            .map(parse_step)
+            .collect()
+    }
+}
+
+impl Run<Vec<Step>, i64> for Day22 {
+    fn part_one(input: &Vec<Step>) -> Result<i64> {
+        let region = Cuboid {
+            min: [-50; 3],
+            max: [50; 3],
+        };
+        let steps: Vec<Step> = input
+            .iter()
+            .filter_map(|s| {
+                s.cuboid
+                    .intersection(&region)
+                    .map(|cuboid| Step { on: s.on, cuboid })
+            })
+            .collect();
+        Ok(count_on(&steps))
+    }
+
+    fn part_two(input: &Vec<Step>) -> Result<i64> {
+        Ok(count_on(input))
+    }
+}
+
+/// Cubes from `min` to `max` on each axis, both included
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Cuboid {
+    min: [i64; 3],
+    max: [i64; 3],
+}
+
+impl Cuboid {
+    pub fn volume(&self) -> i64 {
+        (0..3).map(|i| self.max[i] - self.min[i] + 1).product()
+    }
+
+    pub fn intersection(&self, other: &Cuboid) -> Option<Cuboid> {
+        let min = [0, 1, 2].map(|i| self.min[i].max(other.min[i]));
+        let max = [0, 1, 2].map(|i| self.max[i].min(other.max[i]));
+        match (0..3).all(|i| min[i] <= max[i]) {
+            true => Some(Cuboid { min, max }),
+            false => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Step {
+    on: bool,
+    cuboid: Cuboid,
+}
+
+fn parse_step(line: &str) -> Result<Step> {
+    let (on, ranges) = match line.split_once(' ') {
+        Some(("on", ranges)) => (true, ranges),
+        Some(("off", ranges)) => (false, ranges),
+        _ => bail!("invalid step {:?}", line),
+    };
+    let mut cuboid = Cuboid {
+        min: [0; 3],
+        max: [0; 3],
+    };
+    let ranges: Vec<&str> = ranges.split(',').collect();
+    if ranges.len() != 3 {
+        bail!("expected x, y and z ranges in {:?}", line);
+    }
+    for (i, (range, axis)) in ranges.iter().zip(["x=", "y=", "z="]).enumerate() {
+        match range.strip_prefix(axis).and_then(|r| r.split_once("..")) {
+            Some((min, max)) => {
+                cuboid.min[i] = min.parse()?;
+                cuboid.max[i] = max.parse()?;
+            }
+            None => bail!("invalid range {:?}", range),
+        }
+        if cuboid.min[i] > cuboid.max[i] {
+            bail!("empty range {:?}", range);
+        }
+    }
+    Ok(Step { on, cuboid })
+}
+
+/// Cubes left on after the steps, every overlap is added back with the
+/// opposite sign so the cuboids never have to be split
+pub fn count_on(steps: &[Step]) -> i64 {
+    let mut signed: Vec<(Cuboid, i64)> = Vec::new();
+    for step in steps {
+        let overlaps: Vec<(Cuboid, i64)> = signed
+            .iter()
+            .filter_map(|(c, sign)| c.intersection(&step.cuboid).map(|i| (i, -sign)))
+            .collect();
+        signed.extend(overlaps);
+        if step.on {
+            signed.push((step.cuboid, 1));
+        }
+    }
+    signed.iter().map(|(c, sign)| c.volume() * sign).sum()
+}
+
+#[cfg(test)]
+mod tests_day22 {
+    use super::*;
+    use crate::runner::Executor;
+
+    #[test]
+    fn test_run() -> Result<()> {
+        let (r1, r2) = Day22::run("inputs/day22.test")?;
+        assert_eq!(r1, 590784);
+        // The puzzle gives no part two answer for this example, this one was
+        // checked by splitting the space along every cuboid edge
+        assert_eq!(r2, 39769202357779);
+        Ok(())
+    }
+
+    #[test]
+    fn test_small_example() -> Result<()> {
+        let steps = Day22::parse_input(
+            "on x=10..12,y=10..12,z=10..12
+on x=11..13,y=11..13,z=11..13
+off x=9..11,y=9..11,z=9..11
+on x=10..10,y=10..10,z=10..10",
+        )?;
+        assert_eq!(count_on(&steps[..1]), 27);
+        assert_eq!(count_on(&steps[..2]), 46);
+        assert_eq!(count_on(&steps[..3]), 38);
+        assert_eq!(count_on(&steps), 39);
+        assert!(parse_step("on x=1..0,y=0..1,z=0..1").is_err());
+        assert!(parse_step("toggle x=0..1,y=0..1,z=0..1").is_err());
+        Ok(())
+    }
+}
@@ -0,0 +1,173 @@
+use std::cmp::Reverse;
+use std::collections::{BinaryHeap, HashMap};
+
+use anyhow::{bail, Result};
+
+use crate::runner::{Parse, Run};
+
+pub struct Day23 {}
+
+impl Parse<Burrow> for Day23 {
+    fn parse_input(input: &str) -> Result<Burrow> {
+        let rows = input
+            .lines()
+            .skip(2)
+            .map(|line| {
+                line.chars()
+                    .filter(|c| ('A'..='D').contains(c))
+                    .map(|c| c as u8 - b'A' + 1)
+                    .collect::<Vec<u8>>()
+            })
+            .filter(|row| !row.is_empty())
+            .map(|row| match row.try_into() {
+                Ok(row) => Ok(row),
+                Err(row) => bail!("expected 4 amphipods on a row, got {:?}", row),
+            })
+            .collect::<Result<_>>()?;
+        Ok(Burrow { rows })
+    }
+}
+
+impl Run<Burrow, u32> for Day23 {
+    fn part_one(input: &Burrow) -> Result<u32> {
+        input.organize()
+    }
+
+    fn part_two(input: &Burrow) -> Result<u32> {
+        input.unfold().organize()
+    }
+}
+
+const HALLWAY: usize = 11;
+const DOORS: [usize; 4] = [2, 4, 6, 8];
+const ENERGY: [u32; 4] = [1, 10, 100, 1000];
+
+/// Side rooms of the burrow from the top row down, amphipods are numbered
+/// from 1 for A, and 0 is an empty space
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Burrow {
+    rows: Vec<[u8; 4]>,
+}
+
+impl Burrow {
+    /// The burrow with the two rows hidden in the folded diagram
+    pub fn unfold(&self) -> Burrow {
+        let mut rows = self.rows.clone();
+        let at = rows.len().min(1);
+        rows.splice(at..at, [[4, 3, 2, 1], [4, 2, 1, 3]]);
+        Burrow { rows }
+    }
+
+    /// Least energy needed to get every amphipod into its room
+    pub fn organize(&self) -> Result<u32> {
+        let depth = self.rows.len();
+        let mut start = vec![0; HALLWAY + 4 * depth];
+        for (i, row) in self.rows.iter().enumerate() {
+            (0..4).for_each(|room| start[HALLWAY + room * depth + i] = row[room]);
+        }
+        let mut goal = vec![0; HALLWAY];
+        (0..4).for_each(|room| goal.extend(vec![room as u8 + 1; depth]));
+
+        let mut energies = HashMap::from([(start.clone(), 0)]);
+        let mut queue = BinaryHeap::from([Reverse((0, start))]);
+        while let Some(Reverse((energy, state))) = queue.pop() {
+            if state == goal {
+                return Ok(energy);
+            }
+            if energies.get(&state).is_some_and(|e| *e < energy) {
+                continue;
+            }
+            for (next, cost) in moves(&state, depth) {
+                let total = energy + cost;
+                if energies.get(&next).is_none_or(|e| total < *e) {
+                    energies.insert(next.clone(), total);
+                    queue.push(Reverse((total, next)));
+                }
+            }
+        }
+        bail!("the amphipods can't be organized")
+    }
+}
+
+// Whether the hallway is empty between the two positions, `from` not included
+fn clear(state: &[u8], from: usize, to: usize) -> bool {
+    let range = match from < to {
+        true => from + 1..=to,
+        false => to..=from - 1,
+    };
+    state[range].iter().all(|a| *a == 0)
+}
+
+// Amphipods either leave their room for the hallway or go from the hallway
+// straight into their own room, once nothing else is in it
+fn moves(state: &[u8], depth: usize) -> Vec<(Vec<u8>, u32)> {
+    let mut moves = Vec::new();
+    let room = |r: usize| &state[HALLWAY + r * depth..HALLWAY + (r + 1) * depth];
+    let settled = |r: usize| room(r).iter().all(|a| *a == 0 || *a as usize == r + 1);
+    for (h, amphipod) in state[..HALLWAY].iter().enumerate() {
+        if *amphipod == 0 {
+            continue;
+        }
+        let r = *amphipod as usize - 1;
+        if !settled(r) || !clear(state, h, DOORS[r]) {
+            continue;
+        }
+        let slot = room(r).iter().rposition(|a| *a == 0).unwrap_or(0);
+        let steps = h.abs_diff(DOORS[r]) + slot + 1;
+        let mut next = state.to_vec();
+        next[h] = 0;
+        next[HALLWAY + r * depth + slot] = *amphipod;
+        moves.push((next, steps as u32 * ENERGY[r]));
+    }
+    for r in 0..4 {
+        if settled(r) {
+            continue;
+        }
+        let slot = match room(r).iter().position(|a| *a != 0) {
+            Some(slot) => slot,
+            None => continue,
+        };
+        let amphipod = room(r)[slot];
+        for h in (0..HALLWAY).filter(|h| !DOORS.contains(h)) {
+            if !clear(state, DOORS[r], h) {
+                continue;
+            }
+            let steps = h.abs_diff(DOORS[r]) + slot + 1;
+            let mut next = state.to_vec();
+            next[HALLWAY + r * depth + slot] = 0;
+            next[h] = amphipod;
+            moves.push((next, steps as u32 * ENERGY[amphipod as usize - 1]));
+        }
+    }
+    moves
+}
+
+#[cfg(test)]
+mod tests_day23 {
+    use super::*;
+    use crate::runner::Executor;
+
+    #[test]
+    fn test_run() -> Result<()> {
+        let (r1, r2) = Day23::run("inputs/day23.test")?;
+        assert_eq!(r1, 12521);
+        assert_eq!(r2, 44169);
+        Ok(())
+    }
+
+    #[test]
+    fn test_organize() -> Result<()> {
+        let burrow = Burrow {
+            rows: vec![[1, 2, 3, 4], [1, 2, 3, 4]],
+        };
+        assert_eq!(burrow.organize()?, 0);
+        // B and A swap through the hallway
+        let burrow = Burrow {
+            rows: vec![[2, 1, 3, 4]],
+        };
+        assert_eq!(burrow.organize()?, 46);
+        assert_eq!(burrow.unfold().rows.len(), 3);
+        assert!(Day23::parse_input("#\n#\n###B#C#B###\n").is_err());
+        Ok(())
+    }
+}
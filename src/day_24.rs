@@ -0,0 +1,222 @@
+use std::str::FromStr;
+
+use anyhow::{bail, Error, Result};
+
+use crate::runner::{Parse, Run};
+
+pub struct Day24 {}
+
+impl Parse<Vec<Instruction>> for Day24 {
+    fn parse_input(input: &str) -> Result<Vec<Instruction>> {
+        input
+            .lines()
+            .map(str::trim)
+            .filter(|l| !l.is_empty())
+            .map(str::parse)
+            .collect()
+    }
+}
+
+impl Run<Vec<Instruction>, u64> for Day24 {
+    fn part_one(input: &Vec<Instruction>) -> Result<u64> {
+        model_number(input, true)
+    }
+
+    fn part_two(input: &Vec<Instruction>) -> Result<u64> {
+        model_number(input, false)
+    }
+}
+
+/// Register w, x, y or z, or a number
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Operand {
+    Register(usize),
+    Value(i64),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Instruction {
+    Inp(usize),
+    Add(usize, Operand),
+    Mul(usize, Operand),
+    Div(usize, Operand),
+    Mod(usize, Operand),
+    Eql(usize, Operand),
+}
+
+fn register(s: &str) -> Result<usize> {
+    match s {
+        "w" => Ok(0),
+        "x" => Ok(1),
+        "y" => Ok(2),
+        "z" => Ok(3),
+        _ => bail!("invalid register {:?}", s),
+    }
+}
+
+impl FromStr for Instruction {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let parts: Vec<&str> = s.split_whitespace().collect();
+        let (name, a, b) = match parts.as_slice() {
+            ["inp", a] => return Ok(Instruction::Inp(register(a)?)),
+            [name, a, b] => (*name, register(a)?, b),
+            _ => bail!("invalid instruction {:?}", s),
+        };
+        let b = match register(b) {
+            Ok(r) => Operand::Register(r),
+            Err(_) => Operand::Value(b.parse()?),
+        };
+        match name {
+            "add" => Ok(Instruction::Add(a, b)),
+            "mul" => Ok(Instruction::Mul(a, b)),
+            "div" => Ok(Instruction::Div(a, b)),
+            "mod" => Ok(Instruction::Mod(a, b)),
+            "eql" => Ok(Instruction::Eql(a, b)),
+            _ => bail!("invalid instruction {:?}", s),
+        }
+    }
+}
+
+/// Runs the program reading the inputs in order, returning w, x, y and z
+pub fn run_alu(program: &[Instruction], inputs: &[i64]) -> Result<[i64; 4]> {
+    let mut registers = [0; 4];
+    let mut inputs = inputs.iter();
+    for instruction in program {
+        let value = |b: &Operand| match b {
+            Operand::Register(r) => registers[*r],
+            Operand::Value(v) => *v,
+        };
+        match instruction {
+            Instruction::Inp(a) => {
+                registers[*a] = *inputs.next().ok_or(anyhow::anyhow!("not enough inputs"))?
+            }
+            Instruction::Add(a, b) => registers[*a] += value(b),
+            Instruction::Mul(a, b) => registers[*a] *= value(b),
+            Instruction::Div(a, b) => match value(b) {
+                0 => bail!("division by zero"),
+                b => registers[*a] /= b,
+            },
+            Instruction::Mod(a, b) => match (registers[*a], value(b)) {
+                (a, b) if a < 0 || b <= 0 => bail!("invalid modulo {} % {}", a, b),
+                (_, b) => registers[*a] %= b,
+            },
+            Instruction::Eql(a, b) => registers[*a] = (registers[*a] == value(b)) as i64,
+        }
+    }
+    Ok(registers)
+}
+
+// Every digit is checked by the same 18 instructions, only the ones at 4, 5
+// and 15 change
+const BLOCK: [&str; 18] = [
+    "inp w", "mul x 0", "add x z", "mod x 26", "div z _", "add x _", "eql x w", "eql x 0",
+    "mul y 0", "add y 25", "mul y x", "add y 1", "mul z y", "mul y 0", "add y w", "add y _",
+    "mul y x", "add z y",
+];
+
+/// Parameters of a digit check: whether z is divided by 26, what is added
+/// to x and what is added to y
+fn digit_checks(program: &[Instruction]) -> Result<Vec<(bool, i64, i64)>> {
+    if program.is_empty() || !program.len().is_multiple_of(BLOCK.len()) {
+        bail!("expected blocks of {} instructions", BLOCK.len());
+    }
+    program
+        .chunks(BLOCK.len())
+        .enumerate()
+        .map(|(digit, block)| {
+            for (instruction, expected) in block.iter().zip(BLOCK) {
+                if !expected.ends_with('_') && *instruction != expected.parse()? {
+                    bail!("digit {} doesn't follow the MONAD pattern", digit);
+                }
+            }
+            match (block[4], block[5], block[15]) {
+                (
+                    Instruction::Div(3, Operand::Value(div @ (1 | 26))),
+                    Instruction::Add(1, Operand::Value(x)),
+                    Instruction::Add(2, Operand::Value(y)),
+                ) => Ok((div == 26, x, y)),
+                _ => bail!("digit {} doesn't follow the MONAD pattern", digit),
+            }
+        })
+        .collect()
+}
+
+/// Largest or smallest model number accepted by MONAD, z works as a stack
+/// in base 26 so each digit that pops must match the one that pushed. The
+/// real program has 14 digits but any number of checks works
+pub fn model_number(program: &[Instruction], largest: bool) -> Result<u64> {
+    let checks = digit_checks(program)?;
+    let mut digits = vec![0i64; checks.len()];
+    let mut pushed = Vec::new();
+    for (i, (pop, x, y)) in checks.into_iter().enumerate() {
+        if !pop {
+            pushed.push((i, y));
+            continue;
+        }
+        let (j, y) = pushed
+            .pop()
+            .ok_or(anyhow::anyhow!("digit {} pops nothing", i))?;
+        // digits[i] = digits[j] + diff
+        let diff = y + x;
+        if diff.abs() > 8 {
+            bail!("digits {} and {} can't match", j, i);
+        }
+        digits[j] = match largest {
+            true => 9.min(9 - diff),
+            false => 1.max(1 - diff),
+        };
+        digits[i] = digits[j] + diff;
+    }
+    if !pushed.is_empty() {
+        bail!("z never gets back to 0");
+    }
+    if run_alu(program, &digits)?[3] != 0 {
+        bail!("MONAD rejects {:?}", digits);
+    }
+    Ok(digits.iter().fold(0, |n, d| n * 10 + *d as u64))
+}
+
+#[cfg(test)]
+mod tests_day24 {
+    use super::*;
+    use crate::runner::Executor;
+
+    #[test]
+    fn test_run() -> Result<()> {
+        // Digits 1 and 2 push 1 and 3, 3 pops with -2 so it is one more than
+        // 2, 4 pops with -1 so it equals 1, then 5 pushes 5 and 6 pops with -7
+        // so it is two less than 5
+        let (r1, r2) = Day24::run("inputs/day24.test")?;
+        assert_eq!(r1, 989997);
+        assert_eq!(r2, 112131);
+        let program = Day24::parse_input(include_str!("../inputs/day24.test"))?;
+        for number in [r1, r2] {
+            let digits: Vec<i64> = number
+                .to_string()
+                .chars()
+                .map(|c| c.to_digit(10).unwrap_or(0) as i64)
+                .collect();
+            assert_eq!(run_alu(&program, &digits)?[3], 0);
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_run_alu() -> Result<()> {
+        let negate = Day24::parse_input("inp x\nmul x -1")?;
+        assert_eq!(run_alu(&negate, &[7])?, [0, -7, 0, 0]);
+        let triple = Day24::parse_input("inp z\ninp x\nmul z 3\neql z x")?;
+        assert_eq!(run_alu(&triple, &[2, 6])?[3], 1);
+        assert_eq!(run_alu(&triple, &[2, 5])?[3], 0);
+        let binary = Day24::parse_input(
+            "inp w\nadd z w\nmod z 2\ndiv w 2\nadd y w\nmod y 2\ndiv w 2\nadd x w\nmod x 2\ndiv w 2\nmod w 2",
+        )?;
+        assert_eq!(run_alu(&binary, &[11])?, [1, 0, 1, 1]);
+        assert!(run_alu(&negate, &[]).is_err());
+        assert!("inp q".parse::<Instruction>().is_err());
+        assert!(model_number(&negate, true).is_err());
+        Ok(())
+    }
+}
@@ -0,0 +1,83 @@
+use anyhow::Result;
+
+use crate::grid::Grid;
+use crate::runner::{Parse, RunMut};
+
+pub struct Day25 {}
+
+impl Parse<Grid<char>> for Day25 {
+    fn parse_input(input: &str) -> Result<Grid<char>> {
+        Grid::parse_chars(input)
+    }
+}
+
+impl RunMut<Grid<char>, usize> for Day25 {
+    fn part_one(input: &mut Grid<char>) -> Result<usize> {
+        let mut steps = 1;
+        while step(input) {
+            steps += 1;
+        }
+        Ok(steps)
+    }
+
+    // The last day has a single puzzle
+    fn part_two(_input: &mut Grid<char>) -> Result<usize> {
+        Ok(0)
+    }
+}
+
+/// Moves the east herd and then the south one, wrapping around the edges,
+/// returning whether any sea cucumber moved
+pub fn step(floor: &mut Grid<char>) -> bool {
+    let (width, height) = (floor.width(), floor.height());
+    let east = herd_step(floor, '>', |(row, column)| (row, (column + 1) % width));
+    let south = herd_step(floor, 'v', |(row, column)| ((row + 1) % height, column));
+    east || south
+}
+
+// The whole herd looks at the floor before any of them moves
+fn herd_step(
+    floor: &mut Grid<char>,
+    herd: char,
+    ahead: impl Fn((usize, usize)) -> (usize, usize),
+) -> bool {
+    let moving: Vec<_> = floor
+        .iter()
+        .filter(|(pos, c)| **c == herd && floor[ahead(*pos)] == '.')
+        .map(|(pos, _)| pos)
+        .collect();
+    for pos in &moving {
+        floor[*pos] = '.';
+        floor[ahead(*pos)] = herd;
+    }
+    !moving.is_empty()
+}
+
+#[cfg(test)]
+mod tests_day25 {
+    use super::*;
+    use crate::runner::MutExecutor;
+
+    #[test]
+    fn test_run() -> Result<()> {
+        let (r1, _) = Day25::run("inputs/day25.test")?;
+        assert_eq!(r1, 58);
+        Ok(())
+    }
+
+    #[test]
+    fn test_step() -> Result<()> {
+        let mut floor = Grid::parse_chars("...>>>>>...")?;
+        assert!(step(&mut floor));
+        assert_eq!(floor.to_string(), "...>>>>.>..\n");
+        assert!(step(&mut floor));
+        assert_eq!(floor.to_string(), "...>>>.>.>.\n");
+        let mut floor = Grid::parse_chars("..>\n...\nv..\n")?;
+        // East moves first, so it takes the space the south one wraps into
+        assert!(step(&mut floor));
+        assert_eq!(floor.to_string(), ">..\n...\nv..\n");
+        assert!(step(&mut floor));
+        assert_eq!(floor.to_string(), "v>.\n...\n...\n");
+        Ok(())
+    }
+}
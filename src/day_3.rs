@@ -1,79 +1,310 @@
-use anyhow::Result;
+use std::cmp::Ordering;
+use std::str::FromStr;
+
+use anyhow::{bail, Error, Result};
 
 use crate::runner::{Parse, Run};
 
 pub struct Day3 {}
 
-impl Parse<Vec<Vec<char>>> for Day3 {
-    fn parse_input(input: &str) -> Result<Vec<Vec<char>>> {
-        let r = input.lines().map(|s| s.chars().collect()).collect();
-        Ok(r)
+impl Parse<Report> for Day3 {
+    fn parse_input(input: &str) -> Result<Report> {
+        Report::parse(input)
+    }
+}
+
+impl Run<Report, u128> for Day3 {
+    fn part_one(input: &Report) -> Result<u128> {
+        let (gamma, epsilon) = input.power_rates(&Policy::default())?;
+        Ok(gamma as u128 * epsilon as u128)
+    }
+
+    fn part_two(input: &Report) -> Result<u128> {
+        let (oxygen, co_scrubber) = input.life_support(&Policy::default())?;
+        Ok(oxygen as u128 * co_scrubber as u128)
     }
 }
 
-impl Run<Vec<Vec<char>>, isize> for Day3 {
-    fn part_one(input: &Vec<Vec<char>>) -> Result<isize> {
-        let (gama, epsilon) = binaries_for_power(input)?;
-        let gama = isize::from_str_radix(gama.as_str(), 2)?;
-        let epsilon = isize::from_str_radix(epsilon.as_str(), 2)?;
-        Ok(gama * epsilon)
-    }
-
-    fn part_two(input: &Vec<Vec<char>>) -> Result<isize> {
-        let des = |ones: u32, zeros: u32| -> char {
-            let ch: char = if ones >= zeros { '0' } else { '1' };
-            ch
-        };
-        let oxigen = binaries_for_life(input.to_vec(), des)?;
-        let des = |ones: u32, zeros: u32| -> char {
-            let ch: char = if ones >= zeros { '1' } else { '0' };
-            ch
-        };
-        let co_scrubber = binaries_for_life(input.to_vec(), des)?;
-        let oxigen = isize::from_str_radix(oxigen.as_str(), 2)?;
-        let co_scrubber = isize::from_str_radix(co_scrubber.as_str(), 2)?;
-        Ok(oxigen * co_scrubber)
+/// Which bit wins when a column has as many ones as zeros
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TieBreak {
+    Zero,
+    One,
+}
+
+/// How the bit of each column is chosen
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Criteria {
+    MostCommon(TieBreak),
+    LeastCommon(TieBreak),
+}
+
+impl Criteria {
+    /// Whether the bit chosen for a column with these counts is a one
+    fn picks_one(&self, ones: usize, zeros: usize) -> bool {
+        match (self, ones.cmp(&zeros)) {
+            (Criteria::MostCommon(tie), Ordering::Equal)
+            | (Criteria::LeastCommon(tie), Ordering::Equal) => *tie == TieBreak::One,
+            (Criteria::MostCommon(_), order) => order == Ordering::Greater,
+            (Criteria::LeastCommon(_), order) => order == Ordering::Less,
+        }
+    }
+}
+
+/// Tie breaks of every rate and rating, by default gamma and CO2 pick `0`
+/// while epsilon and oxygen pick `1`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Policy {
+    pub gamma: TieBreak,
+    pub epsilon: TieBreak,
+    pub oxygen: TieBreak,
+    pub co2: TieBreak,
+}
+
+impl Default for Policy {
+    fn default() -> Self {
+        Self {
+            gamma: TieBreak::Zero,
+            epsilon: TieBreak::One,
+            oxygen: TieBreak::One,
+            co2: TieBreak::Zero,
+        }
+    }
+}
+
+// Bits for gamma, epsilon, oxygen and CO2, like "0,1,1,0"
+impl FromStr for Policy {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let ties: Vec<TieBreak> = s
+            .split(',')
+            .map(|t| match t.trim() {
+                "0" => Ok(TieBreak::Zero),
+                "1" => Ok(TieBreak::One),
+                t => bail!("invalid tie break {:?}, expected 0 or 1", t),
+            })
+            .collect::<Result<_>>()?;
+        match ties.as_slice() {
+            [gamma, epsilon, oxygen, co2] => Ok(Self {
+                gamma: *gamma,
+                epsilon: *epsilon,
+                oxygen: *oxygen,
+                co2: *co2,
+            }),
+            _ => bail!("expected <gamma>,<epsilon>,<oxygen>,<co2>"),
+        }
     }
 }
 
-fn count(input: &[Vec<char>], i: usize) -> (u32, u32) {
-    let mut ones: u32 = 0;
-    let mut zeros: u32 = 0;
-    input.iter().for_each(|line| match line[i] {
-        '0' => zeros += 1,
-        '1' => ones += 1,
-        _ => panic!("invalid input"),
-    });
-    (ones, zeros)
+/// Diagnostic report with every row packed on a u64, the first column of a
+/// row is its most significant bit
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Report {
+    width: usize,
+    rows: Vec<u64>,
 }
 
-fn binaries_for_power(input: &[Vec<char>]) -> Result<(String, String)> {
-    let mut gama = String::new();
-    let mut epsilon = String::new();
-    let len = input[0].len();
-    (0..len).for_each(|i| {
-        let (ones, zeros) = count(input, i);
-        if ones > zeros {
-            gama.push('1');
-            epsilon.push('0');
-        } else {
-            gama.push('0');
-            epsilon.push('1');
+impl Report {
+    /// Rows must fit in `width` bits, and the report can be up to 64 bits wide
+    pub fn new(width: usize, rows: Vec<u64>) -> Result<Self> {
+        if width > 64 {
+            bail!("reports can be up to 64 bits wide, got {}", width);
+        }
+        let report = Self { width, rows };
+        if let Some(row) = report.rows.iter().find(|r| **r & !report.mask() != 0) {
+            bail!("row {:b} is wider than {} bits", row, width);
+        }
+        Ok(report)
+    }
+
+    /// Parses one row of `0` and `1` per line, all of them with the same width
+    pub fn parse(input: &str) -> Result<Self> {
+        let lines: Vec<&str> = input
+            .lines()
+            .map(str::trim)
+            .filter(|l| !l.is_empty())
+            .collect();
+        let width = lines.first().map_or(0, |l| l.len());
+        let rows = lines
+            .iter()
+            .enumerate()
+            .map(|(i, line)| {
+                if line.len() != width {
+                    bail!("row {} has {} bits, expected {}", i + 1, line.len(), width);
+                }
+                line.chars().try_fold(0u64, |row, c| match c {
+                    '0' => Ok(row << 1),
+                    '1' => Ok(row << 1 | 1),
+                    _ => bail!("row {}: invalid bit {:?}", i + 1, c),
+                })
+            })
+            .collect::<Result<_>>()?;
+        Self::new(width, rows)
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn rows(&self) -> &[u64] {
+        &self.rows
+    }
+
+    /// Number of ones on each column, from the first one
+    pub fn ones(&self) -> Vec<usize> {
+        let mut ones = vec![0; self.width];
+        for row in &self.rows {
+            let mut bits = *row;
+            while bits != 0 {
+                ones[self.width - 1 - bits.trailing_zeros() as usize] += 1;
+                bits &= bits - 1;
+            }
+        }
+        ones
+    }
+
+    /// Rate built from the bit chosen on every column
+    pub fn rate(&self, criteria: Criteria) -> Result<u64> {
+        if self.rows.is_empty() {
+            bail!("empty report");
+        }
+        let total = self.rows.len();
+        Ok(self.ones().iter().fold(0, |rate, ones| {
+            rate << 1 | criteria.picks_one(*ones, total - ones) as u64
+        }))
+    }
+
+    /// Gamma and epsilon rates
+    pub fn power_rates(&self, policy: &Policy) -> Result<(u64, u64)> {
+        let gamma = self.rate(Criteria::MostCommon(policy.gamma))?;
+        let epsilon = self.rate(Criteria::LeastCommon(policy.epsilon))?;
+        Ok((gamma, epsilon))
+    }
+
+    /// Oxygen generator and CO2 scrubber ratings, walking the same trie
+    pub fn life_support(&self, policy: &Policy) -> Result<(u64, u64)> {
+        let trie = self.trie()?;
+        let oxygen = trie.rating(Criteria::MostCommon(policy.oxygen))?;
+        let co2 = trie.rating(Criteria::LeastCommon(policy.co2))?;
+        Ok((oxygen, co2))
+    }
+
+    pub fn trie(&self) -> Result<Trie> {
+        Trie::new(self)
+    }
+
+    fn mask(&self) -> u64 {
+        match self.width {
+            64 => u64::MAX,
+            width => (1 << width) - 1,
         }
-    });
-    Ok((gama, epsilon))
+    }
 }
 
-fn binaries_for_life(mut input: Vec<Vec<char>>, desition: fn(u32, u32) -> char) -> Result<String> {
-    let mut i = 0;
-    while input.len() > 1 {
-        let (ones, zeros) = count(&input, i);
-        let ch = desition(ones, zeros);
-        input = input.into_iter().filter(|v| v[i] == ch).collect();
-        i += 1;
+/// Binary trie of the report rows, every node counts the rows going through it
+pub struct Trie {
+    width: usize,
+    nodes: Vec<Node>,
+}
+
+// Children are indexes on the nodes, 0 is the root so it means no child
+#[derive(Debug, Clone, Copy, Default)]
+struct Node {
+    children: [u32; 2],
+    count: u32,
+}
+
+impl Trie {
+    pub fn new(report: &Report) -> Result<Self> {
+        let width = report.width();
+        let mut nodes = vec![Node::default()];
+        for row in report.rows() {
+            let mut node = 0;
+            nodes[0].count += 1;
+            for column in 0..width {
+                let bit = (row >> (width - 1 - column) & 1) as usize;
+                node = match nodes[node].children[bit] {
+                    0 => {
+                        let child = u32::try_from(nodes.len())?;
+                        nodes[node].children[bit] = child;
+                        nodes.push(Node::default());
+                        child as usize
+                    }
+                    child => child as usize,
+                };
+                nodes[node].count += 1;
+            }
+        }
+        Ok(Self { width, nodes })
+    }
+
+    /// Number of rows in the trie
+    pub fn len(&self) -> usize {
+        self.nodes[0].count as usize
+    }
+
+    fn count(&self, node: u32) -> usize {
+        match node {
+            0 => 0,
+            node => self.nodes[node as usize].count as usize,
+        }
+    }
+
+    /// Walks down the trie once choosing the bit of each column, a single
+    /// row left is followed to its end, fails if the trie is empty or a
+    /// column leaves no rows
+    pub fn rating(&self, criteria: Criteria) -> Result<u64> {
+        if self.len() == 0 {
+            bail!("empty report");
+        }
+        let (mut node, mut rating) = (0, 0);
+        for column in 0..self.width {
+            let [zero, one] = self.nodes[node].children;
+            let (zeros, ones) = (self.count(zero), self.count(one));
+            let bit = match zeros + ones {
+                1 => ones == 1,
+                _ => criteria.picks_one(ones, zeros),
+            };
+            node = match self.nodes[node].children[bit as usize] {
+                0 => bail!("no rows left for {:?} at column {}", criteria, column),
+                child => child as usize,
+            };
+            rating = rating << 1 | bit as u64;
+        }
+        Ok(rating)
+    }
+
+    /// Prefixes of `length` bits with the number of rows starting with them,
+    /// most common first and ties in prefix order
+    pub fn prefixes(&self, length: usize) -> Result<Vec<(u64, usize)>> {
+        if length > self.width {
+            bail!("prefix of {} bits on a {} bits report", length, self.width);
+        }
+        let mut prefixes = Vec::new();
+        let mut stack = vec![(0, 0, 0)];
+        while let Some((node, prefix, depth)) = stack.pop() {
+            let count = self.nodes[node].count as usize;
+            if depth == length {
+                if count > 0 {
+                    prefixes.push((prefix, count));
+                }
+                continue;
+            }
+            for bit in 0..2 {
+                if let child @ 1.. = self.nodes[node].children[bit] {
+                    stack.push((child as usize, prefix << 1 | bit as u64, depth + 1));
+                }
+            }
+        }
+        prefixes.sort_by(|a, b| b.1.cmp(&a.1).then(a.0.cmp(&b.0)));
+        Ok(prefixes)
+    }
+
+    /// The `k`-th most common prefix of `length` bits, counting from 0
+    pub fn kth_prefix(&self, length: usize, k: usize) -> Result<Option<(u64, usize)>> {
+        Ok(self.prefixes(length)?.get(k).copied())
     }
-    let result: String = input[0].clone().into_iter().collect();
-    Ok(result)
 }
 
 #[cfg(test)]
@@ -110,33 +341,107 @@ mod tests_day3 {
     #[test]
     fn test_binaries_for_power() -> Result<()> {
         let input = Day3::parse_input(INPUT)?;
-        let (gama, epsilon) = binaries_for_power(&input)?;
-        assert_eq!(gama, "10110");
-        assert_eq!(epsilon, "01001");
+        assert_eq!(input.ones(), vec![7, 5, 8, 7, 5]);
+        let (gamma, epsilon) = input.power_rates(&Policy::default())?;
+        assert_eq!(gamma, 0b10110);
+        assert_eq!(epsilon, 0b01001);
         Ok(())
     }
 
     #[test]
     fn test_binaries_for_life_oxygen() -> Result<()> {
         let input = Day3::parse_input(INPUT)?;
-        let des = |ones: u32, zeros: u32| -> char {
-            let ch: char = if ones >= zeros { '1' } else { '0' };
-            ch
-        };
-        let ox = binaries_for_life(input, des)?;
-        assert_eq!(ox, "10111");
+        let oxygen = input.trie()?.rating(Criteria::MostCommon(TieBreak::One))?;
+        assert_eq!(oxygen, 0b10111);
         Ok(())
     }
 
     #[test]
     fn test_binaries_for_life_co() -> Result<()> {
         let input = Day3::parse_input(INPUT)?;
-        let des = |ones: u32, zeros: u32| -> char {
-            let ch: char = if ones >= zeros { '0' } else { '1' };
-            ch
-        };
-        let co = binaries_for_life(input, des)?;
-        assert_eq!(co, "01010");
+        let co2 = input
+            .trie()?
+            .rating(Criteria::LeastCommon(TieBreak::Zero))?;
+        assert_eq!(co2, 0b01010);
+        assert_eq!(input.life_support(&Policy::default())?, (0b10111, 0b01010));
+        Ok(())
+    }
+
+    #[test]
+    fn test_tie_breaks() -> Result<()> {
+        let report = Report::parse("10\n01\n11\n00\n")?;
+        assert_eq!(report.power_rates(&Policy::default())?, (0b00, 0b11));
+        let policy: Policy = "1,0,0,1".parse()?;
+        assert_eq!(report.power_rates(&policy)?, (0b11, 0b00));
+        assert_eq!(report.life_support(&Policy::default())?, (0b11, 0b00));
+        assert_eq!(report.life_support(&policy)?, (0b00, 0b11));
+        assert!("1,0,0".parse::<Policy>().is_err());
+        assert!("1,0,2,0".parse::<Policy>().is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_undetermined_ratings() -> Result<()> {
+        let empty = Report::parse("")?;
+        assert!(empty.power_rates(&Policy::default()).is_err());
+        assert!(empty.life_support(&Policy::default()).is_err());
+        // Every row starts with a one, so the least common bit has no rows
+        let report = Report::parse("10\n11\n")?;
+        assert_eq!(
+            report
+                .trie()?
+                .rating(Criteria::LeastCommon(TieBreak::Zero))
+                .unwrap_err()
+                .to_string(),
+            "no rows left for LeastCommon(Zero) at column 0"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_wide_report() -> Result<()> {
+        let first = "1".repeat(64);
+        let second = format!("0{}", "1".repeat(63));
+        let third = format!("1{}", "0".repeat(63));
+        let report = Report::parse(&format!("{}\n{}\n{}\n", first, second, third))?;
+        assert_eq!(report.width(), 64);
+        assert_eq!(report.rows()[0], u64::MAX);
+        assert_eq!(report.power_rates(&Policy::default())?, (u64::MAX, 0));
+        assert_eq!(
+            report.life_support(&Policy::default())?,
+            (u64::MAX, u64::MAX >> 1)
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_invalid_report() {
+        let error = |input: &str| Report::parse(input).unwrap_err().to_string();
+        assert_eq!(error("0101\n011\n"), "row 2 has 3 bits, expected 4");
+        assert_eq!(error("0121\n"), "row 1: invalid bit '2'");
+        assert!(Report::parse(&"1".repeat(65)).is_err());
+        assert!(Report::new(3, vec![0b1000]).is_err());
+    }
+
+    #[test]
+    fn test_trie() -> Result<()> {
+        let report = Day3::parse_input(INPUT)?;
+        let trie = report.trie()?;
+        assert_eq!(trie.len(), 12);
+        assert_eq!(trie.prefixes(1)?, vec![(0b1, 7), (0b0, 5)]);
+        assert_eq!(
+            trie.prefixes(2)?,
+            vec![(0b10, 4), (0b00, 3), (0b11, 3), (0b01, 2)]
+        );
+        assert_eq!(trie.kth_prefix(2, 1)?, Some((0b00, 3)));
+        assert_eq!(trie.kth_prefix(2, 4)?, None);
+        assert_eq!(trie.prefixes(0)?, vec![(0, 12)]);
+        assert!(trie.prefixes(6).is_err());
+        assert_eq!(trie.rating(Criteria::MostCommon(TieBreak::One))?, 0b10111);
+        // Duplicated rows never split, so the least common bit has none
+        let trie = Report::parse("101\n101\n")?.trie()?;
+        assert!(trie.rating(Criteria::LeastCommon(TieBreak::Zero)).is_err());
+        assert_eq!(trie.rating(Criteria::MostCommon(TieBreak::Zero))?, 0b101);
         Ok(())
     }
 }
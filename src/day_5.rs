@@ -1,6 +1,6 @@
-use std::{collections::HashMap, str::FromStr};
+use std::{collections::HashMap, io::BufRead, str::FromStr};
 
-use crate::runner::{Parse, Run};
+use crate::runner::{Parse, Run, RunStream};
 use anyhow::{Error, Result};
 use std::cmp::Ordering;
 
@@ -8,41 +8,60 @@ pub struct Day5 {}
 
 impl Parse<Vec<Line>> for Day5 {
     fn parse_input(input: &str) -> Result<Vec<Line>> {
-        let r = input
-            .lines()
-            .filter_map(|l| {
-                let (p1, p2) = l.split_once(" -> ")?;
-                let start = Point::from_str(p1).ok()?;
-                let end = Point::from_str(p2).ok()?;
-                let direction = Direction::from_points(&start, &end)?;
-                let line = Line {
-                    start,
-                    end,
-                    direction,
-                };
-                Some(line)
-            })
-            .collect();
+        let r = input.lines().filter_map(parse_line).collect();
         Ok(r)
     }
 }
 
-impl Run<Vec<Line>, u16> for Day5 {
-    fn part_one(input: &Vec<Line>) -> Result<u16> {
+impl Run<Vec<Line>, usize> for Day5 {
+    fn part_one(input: &Vec<Line>) -> Result<usize> {
         let filter = |x: &&Line| x.direction.is_straight();
-        let diagram = create_diagram(input, filter);
-        let lim: u16 = 2;
-        Ok(diagram.into_iter().filter(|(_, v)| *v >= lim).count() as u16)
+        Ok(overlaps(&create_diagram(input, filter)))
     }
 
-    fn part_two(input: &Vec<Line>) -> Result<u16> {
+    fn part_two(input: &Vec<Line>) -> Result<usize> {
         let filter = |_: &&Line| true;
-        let diagram = create_diagram(input, filter);
-        let lim: u16 = 2;
-        Ok(diagram.into_iter().filter(|(_, v)| *v >= lim).count() as u16)
+        Ok(overlaps(&create_diagram(input, filter)))
     }
 }
 
+// Only the diagrams are kept, with one entry per point covered by a line. They
+// don't grow with the number of lines but with the area they cover, up to
+// 65536 x 65536 points for u16 coordinates
+impl RunStream<usize> for Day5 {
+    fn run_lines(input: impl BufRead) -> Result<(usize, usize)> {
+        let mut straight = HashMap::new();
+        let mut all = HashMap::new();
+        for line in input.lines() {
+            if let Some(l) = parse_line(&line?) {
+                if l.direction.is_straight() {
+                    draw_line(&mut straight, &l);
+                }
+                draw_line(&mut all, &l);
+            }
+        }
+        Ok((overlaps(&straight), overlaps(&all)))
+    }
+}
+
+// Points where at least two lines cross
+fn overlaps(diagram: &HashMap<(i32, i32), usize>) -> usize {
+    diagram.values().filter(|v| **v >= 2).count()
+}
+
+fn parse_line(l: &str) -> Option<Line> {
+    let (p1, p2) = l.split_once(" -> ")?;
+    let start = Point::from_str(p1).ok()?;
+    let end = Point::from_str(p2).ok()?;
+    let direction = Direction::from_points(&start, &end)?;
+    let line = Line {
+        start,
+        end,
+        direction,
+    };
+    Some(line)
+}
+
 #[derive(Debug)]
 struct Point {
     x: u16,
@@ -105,75 +124,28 @@ impl Direction {
     }
 }
 
-// TODO: There gotta be a better way to do this
-// Maybe use zip with a reverse iterator?
-fn create_diagram(lines: &[Line], filter: fn(&&Line) -> bool) -> HashMap<String, u16> {
+fn create_diagram(lines: &[Line], filter: fn(&&Line) -> bool) -> HashMap<(i32, i32), usize> {
     let mut diagram = HashMap::new();
     for l in lines.iter().filter(filter) {
-        let start: u16;
-        let end: u16;
-        let on_x: bool;
-        let mut increase: bool = false;
-        let mut special_y: u16 = 0;
-        match l.direction {
-            Direction::Up | Direction::Down => {
-                start = std::cmp::min(l.start.y, l.end.y);
-                end = std::cmp::max(l.start.y, l.end.y);
-                on_x = false;
-            }
-            Direction::Left | Direction::Right => {
-                start = std::cmp::min(l.start.x, l.end.x);
-                end = std::cmp::max(l.start.x, l.end.x);
-                on_x = true;
-            }
-            Direction::RightUp | Direction::LeftDown => {
-                start = std::cmp::min(l.start.x, l.end.x);
-                end = std::cmp::max(l.start.x, l.end.x);
-                special_y = std::cmp::max(l.start.y, l.end.y);
-                on_x = true;
-                increase = false;
-            }
-            Direction::RightDown | Direction::LeftUp => {
-                start = std::cmp::min(l.start.x, l.end.x);
-                end = std::cmp::max(l.start.x, l.end.x);
-                special_y = std::cmp::min(l.start.y, l.end.y);
-                on_x = true;
-                increase = true;
-            }
-        }
-        (start..=end).for_each(|i| {
-            let key: String;
-            match (on_x, l.direction.is_straight()) {
-                // If is diagonal both coordinates have to move with different starts
-                // and directions
-                (true, false) => {
-                    key = format!("{},{}", i, special_y);
-
-                    //so this increase or decrease y depending of the direction
-                    match (increase, special_y) {
-                        (true, _) => special_y += 1,
-                        (false, y) if y != 0 => special_y -= 1,
-                        _ => {}
-                    }
-                }
-                (true, true) => key = format!("{},{}", i, l.start.y),
-                (false, true) => key = format!("{},{}", l.start.x, i),
-                _ => panic!("invalid case"),
-            }
-            match diagram.get_mut(&key) {
-                Some(v) => *v += 1,
-                None => {
-                    diagram.insert(key, 1);
-                }
-            }
-        });
+        draw_line(&mut diagram, l);
     }
     diagram
 }
 
+// Lines are straight or at 45 degrees, so both coordinates move by at most 1
+fn draw_line(diagram: &mut HashMap<(i32, i32), usize>, l: &Line) {
+    let (x, y) = (i32::from(l.start.x), i32::from(l.start.y));
+    let (dx, dy) = (i32::from(l.end.x) - x, i32::from(l.end.y) - y);
+    for i in 0..=dx.abs().max(dy.abs()) {
+        *diagram
+            .entry((x + i * dx.signum(), y + i * dy.signum()))
+            .or_default() += 1;
+    }
+}
+
 #[cfg(test)]
 mod tests_day5 {
-    use crate::runner::Executor;
+    use crate::runner::{Executor, StreamExecutor};
 
     use super::*;
 
@@ -201,6 +173,14 @@ mod tests_day5 {
         Ok(())
     }
 
+    #[test]
+    fn test_run_stream() -> Result<()> {
+        let (r1, r2) = Day5::run_stream("inputs/day5.test")?;
+        assert_eq!(r1, 5);
+        assert_eq!(r2, 12);
+        Ok(())
+    }
+
     #[test]
     fn test_direction_from_points() {
         let p1 = Point { x: 0, y: 5 };
@@ -265,10 +245,10 @@ mod tests_day5 {
         let filter = |x: &&Line| x.direction.is_straight();
         let diagram = create_diagram(&input, filter);
         assert_eq!(diagram.len(), 21);
-        assert_eq!(diagram.get("0,9"), Some(&2));
-        assert_eq!(diagram.get("7,0"), Some(&1));
-        assert_eq!(diagram.get("9,4"), Some(&1));
-        assert_eq!(diagram.get("0,0"), None);
+        assert_eq!(diagram.get(&(0, 9)), Some(&2));
+        assert_eq!(diagram.get(&(7, 0)), Some(&1));
+        assert_eq!(diagram.get(&(9, 4)), Some(&1));
+        assert_eq!(diagram.get(&(0, 0)), None);
         Ok(())
     }
 
@@ -278,12 +258,23 @@ mod tests_day5 {
         let filter = |_: &&Line| true;
         let diagram = create_diagram(&input, filter);
         assert_eq!(diagram.len(), 39);
-        assert_eq!(diagram.get("0,9"), Some(&2));
-        assert_eq!(diagram.get("7,0"), Some(&1));
-        assert_eq!(diagram.get("9,4"), Some(&1));
-        assert_eq!(diagram.get("0,0"), Some(&1));
-        assert_eq!(diagram.get("4,4"), Some(&3));
-        assert_eq!(diagram.get("0,1"), None);
+        assert_eq!(diagram.get(&(0, 9)), Some(&2));
+        assert_eq!(diagram.get(&(7, 0)), Some(&1));
+        assert_eq!(diagram.get(&(9, 4)), Some(&1));
+        assert_eq!(diagram.get(&(0, 0)), Some(&1));
+        assert_eq!(diagram.get(&(4, 4)), Some(&3));
+        assert_eq!(diagram.get(&(0, 1)), None);
+        Ok(())
+    }
+
+    #[test]
+    fn test_many_overlaps() -> Result<()> {
+        // More lines on a point than a u16 counter could hold
+        let input = "0,0 -> 2,2\n".repeat(70_000);
+        assert_eq!(Day5::run_lines(input.as_bytes())?, (0, 3));
+        let lines = Day5::parse_input(&input)?;
+        let diagram = create_diagram(&lines, |_| true);
+        assert_eq!(diagram.get(&(1, 1)), Some(&70_000));
         Ok(())
     }
 }
@@ -1,43 +1,113 @@
 use crate::runner::{Parse, RunMut};
 
-use anyhow::Result;
+use std::str::FromStr;
+
+use anyhow::{bail, Error, Result};
 
 pub struct Day6 {}
 
 impl Parse<Vec<usize>> for Day6 {
     fn parse_input(input: &str) -> Result<Vec<usize>> {
-        Ok(input
-            .trim()
-            .split(',')
-            .filter_map(|n| n.parse::<usize>().ok())
-            .fold([0; 9], |mut map, n| {
-                map[n] += 1;
-                map
-            })
-            .to_vec())
+        LifeCycle::default().parse_fish(input)
     }
 }
 
 impl RunMut<Vec<usize>, usize> for Day6 {
     fn part_one(input: &mut Vec<usize>) -> Result<usize> {
-        Ok(count_fish(input, 80))
+        Ok(count_fish(input, 80, &LifeCycle::default()))
     }
 
     fn part_two(input: &mut Vec<usize>) -> Result<usize> {
-        Ok(count_fish(input, 256))
+        Ok(count_fish(input, 256, &LifeCycle::default()))
+    }
+}
+
+/// Timers of a lanternfish species
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct LifeCycle {
+    /// Timer of a fish after it creates a new one
+    reset: usize,
+    /// Timer of a newly created fish
+    newborn: usize,
+}
+
+impl Default for LifeCycle {
+    fn default() -> Self {
+        Self {
+            reset: 6,
+            newborn: 8,
+        }
+    }
+}
+
+/// Highest reset or newborn timer, the matrix simulation needs a row and
+/// a column per timer
+pub const MAX_TIMER: usize = 255;
+
+impl LifeCycle {
+    pub fn new(reset: usize, newborn: usize) -> Result<Self> {
+        if reset > MAX_TIMER || newborn > MAX_TIMER {
+            bail!("timers can't be over {}", MAX_TIMER);
+        }
+        Ok(Self { reset, newborn })
+    }
+
+    /// Number of different timers a fish can have
+    pub fn buckets(&self) -> usize {
+        self.reset.max(self.newborn) + 1
+    }
+
+    /// Parses a list of timers into the count of fish per timer
+    pub fn parse_fish(&self, input: &str) -> Result<Vec<usize>> {
+        let map =
+            self.parse_timers(input)?
+                .into_iter()
+                .fold(vec![0; self.buckets()], |mut map, n| {
+                    map[n] += 1;
+                    map
+                });
+        Ok(map)
+    }
+
+    /// Parses a list of timers, one per fish
+    pub fn parse_timers(&self, input: &str) -> Result<Vec<usize>> {
+        input
+            .trim()
+            .split(',')
+            .map(|n| {
+                let n: usize = n
+                    .trim()
+                    .parse()
+                    .map_err(|_| anyhow::anyhow!("invalid timer {:?}", n))?;
+                if n >= self.buckets() {
+                    bail!("timer {} is out of range 0..{}", n, self.buckets());
+                }
+                Ok(n)
+            })
+            .collect()
+    }
+}
+
+impl FromStr for LifeCycle {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let (reset, newborn) = s
+            .split_once(',')
+            .ok_or(anyhow::anyhow!("expected <reset>,<newborn>"))?;
+        Self::new(reset.parse()?, newborn.parse()?)
     }
 }
 
 // First attemp, not efficient enough for part 2
-#[allow(dead_code)]
-fn change_fish(input: &mut Vec<u16>, days: u16) {
+fn change_fish(input: &mut Vec<usize>, days: u16, cycle: &LifeCycle) {
     (0..days).for_each(|_| {
         let mut new_fishes = Vec::new();
         for fish in input.iter_mut() {
             match fish {
                 0 => {
-                    *fish = 6;
-                    new_fishes.push(8);
+                    *fish = cycle.reset;
+                    new_fishes.push(cycle.newborn);
                 }
                 _ => *fish -= 1,
             }
@@ -48,16 +118,210 @@ fn change_fish(input: &mut Vec<u16>, days: u16) {
     });
 }
 
-fn count_fish(input: &mut [usize], days: usize) -> usize {
+fn count_fish(input: &mut [usize], days: usize, cycle: &LifeCycle) -> usize {
+    (0..days).for_each(|_| next_day(input, cycle));
+
+    input.iter().sum()
+}
+
+fn next_day(input: &mut [usize], cycle: &LifeCycle) {
+    let last = input.len() - 1;
+    // Move each count to one less day
+    // Making the fish at 0 going to the last timer
+    input.rotate_left(1);
+    // Move the fish that were on 0 to the reset timer, with their newborns
+    let spawning = std::mem::take(&mut input[last]);
+    input[cycle.reset] += spawning;
+    input[cycle.newborn] += spawning;
+}
+
+/// Fish per timer at the end of a day
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Population {
+    pub day: usize,
+    pub buckets: Vec<usize>,
+}
+
+impl Population {
+    pub fn total(&self) -> usize {
+        self.buckets.iter().sum()
+    }
+}
+
+/// Population of every day from the initial state up to `days`
+pub fn population_series(input: &[usize], days: usize, cycle: &LifeCycle) -> Vec<Population> {
+    let mut buckets = input.to_vec();
+    let mut series = Vec::with_capacity(days + 1);
+    for day in 0..=days {
+        if day > 0 {
+            next_day(&mut buckets, cycle);
+        }
+        series.push(Population {
+            day,
+            buckets: buckets.clone(),
+        });
+    }
+    series
+}
+
+/// Total population of every day up to `days` using `change_fish`,
+/// to compare with the bucket based simulation
+pub fn naive_population_series(timers: &[usize], days: usize, cycle: &LifeCycle) -> Vec<usize> {
+    let mut fish = timers.to_vec();
+    let mut series = vec![fish.len()];
     (0..days).for_each(|_| {
-        // Move each count to one less day
-        // Making the fish at 0 becoming 8
-        input.rotate_left(1);
-        // Acumulate the fish that were on 0, now on 8 to 6
-        input[6] += input[8];
+        change_fish(&mut fish, 1, cycle);
+        series.push(fish.len());
     });
+    series
+}
 
-    input.iter().sum()
+/// Writes the series as CSV with a column per timer,
+/// plus the naive totals when given
+pub fn series_to_csv(series: &[Population], naive: Option<&[usize]>) -> String {
+    let buckets = series.first().map_or(0, |p| p.buckets.len());
+    let mut csv = String::from("day,total");
+    (0..buckets).for_each(|t| csv.push_str(&format!(",timer_{}", t)));
+    if naive.is_some() {
+        csv.push_str(",naive");
+    }
+    csv.push('\n');
+    for population in series {
+        csv.push_str(&format!("{},{}", population.day, population.total()));
+        population
+            .buckets
+            .iter()
+            .for_each(|count| csv.push_str(&format!(",{}", count)));
+        if let Some(count) = naive.and_then(|n| n.get(population.day)) {
+            csv.push_str(&format!(",{}", count));
+        }
+        csv.push('\n');
+    }
+    csv
+}
+
+/// How the matrix based simulation does its arithmetic
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Arithmetic {
+    /// Exact count on a u128, not arbitrary precision: every step is checked
+    /// and a population that doesn't fit is an error, never wrapped
+    Exact,
+    /// Count modulo the given number
+    Modulo(u64),
+}
+
+impl Arithmetic {
+    fn add(self, a: u128, b: u128) -> Option<u128> {
+        match self {
+            Arithmetic::Exact => a.checked_add(b),
+            Arithmetic::Modulo(m) => Some((a + b) % m as u128),
+        }
+    }
+
+    fn mul(self, a: u128, b: u128) -> Option<u128> {
+        match self {
+            Arithmetic::Exact => a.checked_mul(b),
+            // Both values are already reduced, so they are below 2^64
+            Arithmetic::Modulo(m) => Some(a * b % m as u128),
+        }
+    }
+
+    fn reduce(self, a: u128) -> u128 {
+        match self {
+            Arithmetic::Exact => a,
+            Arithmetic::Modulo(m) => a % m as u128,
+        }
+    }
+}
+
+type Matrix = Vec<Vec<u128>>;
+
+/// Counts the fish after `days` raising the transition matrix to the `days` power,
+/// so it runs in O(log days) instead of simulating every day like `count_fish`.
+/// Past about 2^128 fish only `Arithmetic::Modulo` gives an answer
+pub fn fast_count_fish(
+    input: &[usize],
+    days: u64,
+    cycle: &LifeCycle,
+    arithmetic: Arithmetic,
+) -> Result<u128> {
+    if arithmetic == Arithmetic::Modulo(0) {
+        bail!("modulo has to be greater than 0");
+    }
+    if input.len() != cycle.buckets() {
+        bail!("expected {} timers, got {}", cycle.buckets(), input.len());
+    }
+    let overflow = || {
+        anyhow::anyhow!(
+            "population after {} days overflows a u128, count it modulo a number instead",
+            days
+        )
+    };
+    let matrix = matrix_pow(transition_matrix(cycle), days, arithmetic).ok_or_else(overflow)?;
+    let mut total = 0;
+    for row in matrix.iter() {
+        for (cell, fish) in row.iter().zip(input) {
+            let count = arithmetic
+                .mul(*cell, arithmetic.reduce(*fish as u128))
+                .ok_or_else(overflow)?;
+            total = arithmetic.add(total, count).ok_or_else(overflow)?;
+        }
+    }
+    Ok(total)
+}
+
+// Each row is a timer for the next day, and each column the timer it comes from
+fn transition_matrix(cycle: &LifeCycle) -> Matrix {
+    let len = cycle.buckets();
+    let mut matrix = vec![vec![0; len]; len];
+    // Every timer goes down by one
+    (0..len - 1).for_each(|i| matrix[i][i + 1] = 1);
+    // The fish at 0 reset and create a new one
+    matrix[cycle.reset][0] += 1;
+    matrix[cycle.newborn][0] += 1;
+    matrix
+}
+
+fn matrix_pow(mut base: Matrix, mut exp: u64, arithmetic: Arithmetic) -> Option<Matrix> {
+    let len = base.len();
+    let mut result: Matrix = (0..len)
+        .map(|i| {
+            (0..len)
+                .map(|j| arithmetic.reduce((i == j) as u128))
+                .collect()
+        })
+        .collect();
+    base = base
+        .into_iter()
+        .map(|row| row.into_iter().map(|c| arithmetic.reduce(c)).collect())
+        .collect();
+    while exp > 0 {
+        if exp & 1 == 1 {
+            result = matrix_mul(&result, &base, arithmetic)?;
+        }
+        exp >>= 1;
+        if exp > 0 {
+            base = matrix_mul(&base, &base, arithmetic)?;
+        }
+    }
+    Some(result)
+}
+
+fn matrix_mul(a: &Matrix, b: &Matrix, arithmetic: Arithmetic) -> Option<Matrix> {
+    let len = a.len();
+    let mut result = vec![vec![0; len]; len];
+    for i in 0..len {
+        for k in 0..len {
+            if a[i][k] == 0 {
+                continue;
+            }
+            for j in 0..len {
+                let value = arithmetic.mul(a[i][k], b[k][j])?;
+                result[i][j] = arithmetic.add(result[i][j], value)?;
+            }
+        }
+    }
+    Some(result)
 }
 
 #[cfg(test)]
@@ -85,14 +349,145 @@ mod tests {
     #[test]
     fn test_count_fish() -> Result<()> {
         let mut input = Day6::parse_input(INPUT)?;
-        let count = count_fish(&mut input, 2);
+        let count = count_fish(&mut input, 2, &LifeCycle::default());
         assert_eq!(count, 6);
         let mut input = Day6::parse_input(INPUT)?;
-        let count = count_fish(&mut input, 3);
+        let count = count_fish(&mut input, 3, &LifeCycle::default());
         assert_eq!(count, 7);
         let mut input = Day6::parse_input(INPUT)?;
-        let count = count_fish(&mut input, 18);
+        let count = count_fish(&mut input, 18, &LifeCycle::default());
         assert_eq!(count, 26);
         Ok(())
     }
+
+    #[test]
+    fn test_fast_count_fish() -> Result<()> {
+        let input = Day6::parse_input(INPUT)?;
+        let count = fast_count_fish(&input, 18, &LifeCycle::default(), Arithmetic::Exact)?;
+        assert_eq!(count, 26);
+        let count = fast_count_fish(&input, 80, &LifeCycle::default(), Arithmetic::Exact)?;
+        assert_eq!(count, 5934);
+        let count = fast_count_fish(&input, 256, &LifeCycle::default(), Arithmetic::Exact)?;
+        assert_eq!(count, 26984457539);
+        let count = fast_count_fish(&input, 0, &LifeCycle::default(), Arithmetic::Exact)?;
+        assert_eq!(count, 5);
+        Ok(())
+    }
+
+    #[test]
+    fn test_fast_count_fish_modulo() -> Result<()> {
+        let input = Day6::parse_input(INPUT)?;
+        let count = fast_count_fish(
+            &input,
+            256,
+            &LifeCycle::default(),
+            Arithmetic::Modulo(1_000_000_007),
+        )?;
+        assert_eq!(count, 26984457539 % 1_000_000_007);
+        let count = fast_count_fish(
+            &input,
+            1_000_000_000_000,
+            &LifeCycle::default(),
+            Arithmetic::Modulo(u64::MAX),
+        )?;
+        assert!(count < u64::MAX as u128);
+        assert!(fast_count_fish(&input, 80, &LifeCycle::default(), Arithmetic::Modulo(0)).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_fast_count_fish_overflow() -> Result<()> {
+        let input = Day6::parse_input(INPUT)?;
+        let error = fast_count_fish(&input, 1_000_000, &LifeCycle::default(), Arithmetic::Exact)
+            .unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "population after 1000000 days overflows a u128, count it modulo a number instead"
+        );
+        // Small enough to fit, big enough to overflow a u64
+        let count = fast_count_fish(&input, 500, &LifeCycle::default(), Arithmetic::Exact)?;
+        assert!(count > u64::MAX as u128);
+        Ok(())
+    }
+
+    #[test]
+    fn test_parse_invalid_timers() -> Result<()> {
+        assert!(Day6::parse_input("3,4,9,1").is_err());
+        assert!(Day6::parse_input("3,a,1").is_err());
+        let cycle = LifeCycle::new(2, 3)?;
+        assert!(cycle.parse_fish("1,2,3,4").is_err());
+        assert_eq!(cycle.parse_fish("1,2,3,3")?, vec![0, 1, 1, 2]);
+        Ok(())
+    }
+
+    #[test]
+    fn test_invalid_life_cycle() -> Result<()> {
+        assert_eq!("6,8".parse::<LifeCycle>()?, LifeCycle::default());
+        assert_eq!("255,0".parse::<LifeCycle>()?.buckets(), 256);
+        assert!("18446744073709551615,8".parse::<LifeCycle>().is_err());
+        assert!("3000000,8".parse::<LifeCycle>().is_err());
+        assert!(LifeCycle::new(6, 256).is_err());
+        assert!("6".parse::<LifeCycle>().is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_custom_life_cycle() -> Result<()> {
+        let cycle = LifeCycle::new(2, 3)?;
+        let mut naive = vec![1, 2, 3, 0];
+        change_fish(&mut naive, 20, &cycle);
+        let mut input = cycle.parse_fish("1,2,3,0")?;
+        let count = count_fish(&mut input.clone(), 20, &cycle);
+        assert_eq!(count, naive.len());
+        let fast = fast_count_fish(&input, 20, &cycle, Arithmetic::Exact)?;
+        assert_eq!(fast, naive.len() as u128);
+        // Newborns with a shorter timer than the reset
+        let cycle = LifeCycle::new(4, 1)?;
+        let mut naive = vec![4, 0, 1];
+        change_fish(&mut naive, 15, &cycle);
+        input = cycle.parse_fish("4,0,1")?;
+        let fast = fast_count_fish(&input, 15, &cycle, Arithmetic::Exact)?;
+        assert_eq!(count_fish(&mut input, 15, &cycle), naive.len());
+        assert_eq!(fast, naive.len() as u128);
+        Ok(())
+    }
+
+    #[test]
+    fn test_population_series() -> Result<()> {
+        let cycle = LifeCycle::default();
+        let input = Day6::parse_input(INPUT)?;
+        let series = population_series(&input, 18, &cycle);
+        assert_eq!(series.len(), 19);
+        assert_eq!(series[0].total(), 5);
+        assert_eq!(series[2].total(), 6);
+        assert_eq!(series[3].total(), 7);
+        assert_eq!(series[18].total(), 26);
+        assert_eq!(series[2].buckets, vec![1, 2, 1, 0, 0, 0, 1, 0, 1]);
+
+        let timers = cycle.parse_timers(INPUT)?;
+        let naive = naive_population_series(&timers, 18, &cycle);
+        let totals: Vec<usize> = series.iter().map(|p| p.total()).collect();
+        assert_eq!(totals, naive);
+        Ok(())
+    }
+
+    #[test]
+    fn test_series_to_csv() -> Result<()> {
+        let cycle = LifeCycle::new(2, 3)?;
+        let input = cycle.parse_fish("0,3")?;
+        let series = population_series(&input, 2, &cycle);
+        let csv = series_to_csv(&series, None);
+        assert_eq!(
+            csv,
+            "day,total,timer_0,timer_1,timer_2,timer_3\n\
+             0,2,1,0,0,1\n\
+             1,3,0,0,2,1\n\
+             2,3,0,2,1,0\n"
+        );
+        let naive = naive_population_series(&[0, 3], 2, &cycle);
+        let csv = series_to_csv(&series, Some(&naive));
+        assert!(csv.starts_with("day,total,timer_0,timer_1,timer_2,timer_3,naive\n"));
+        assert!(csv.ends_with("2,3,0,2,1,0,3\n"));
+        Ok(())
+    }
 }
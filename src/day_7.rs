@@ -1,4 +1,4 @@
-use anyhow::Result;
+use anyhow::{bail, Result};
 
 use crate::runner::{Parse, RunMut};
 
@@ -16,58 +16,209 @@ impl Parse<Vec<i32>> for Day7 {
 
 impl RunMut<Vec<i32>, i32> for Day7 {
     fn part_one(input: &mut Vec<i32>) -> Result<i32> {
-        input.sort_unstable();
-        let mid = input[input.len() / 2];
-        let fuel = input.iter().fold(0, |acc, n| {
-            let diff = (mid - n).abs();
-            acc + diff
-        });
-        Ok(fuel)
+        let alignment = Crabs::new(input).align(&Linear)?;
+        Ok(alignment.fuel.try_into()?)
     }
 
     fn part_two(input: &mut Vec<i32>) -> Result<i32> {
-        input.sort_unstable();
-        let mid = input[input.len() / 2];
-        let left = mid - 1;
-        let right = mid + 1;
-        let mid_fuel = expensive_fuel(input, mid);
-        let left_fuel = expensive_fuel(input, left);
-        let right_fuel = expensive_fuel(input, right);
-        if mid_fuel < right_fuel && mid_fuel < left_fuel {
-            return Ok(mid_fuel);
-        }
-        let mut control: i32;
-        let decrease: bool;
-        let mut current_optimal: i32;
-        if left_fuel < right_fuel {
-            control = left;
-            decrease = true;
-            current_optimal = left_fuel;
-        } else {
-            control = right;
-            decrease = false;
-            current_optimal = right_fuel;
+        let alignment = Crabs::new(input).align(&Triangular)?;
+        Ok(alignment.fuel.try_into()?)
+    }
+}
+
+/// How much fuel a crab spends to move, any closure `Fn(i64) -> i64` works as one.
+/// The cost has to be convex on the distance so `Crabs::align` finds the minimum
+pub trait CostModel {
+    /// Fuel spent by a crab moving `distance` steps
+    fn cost(&self, distance: i64) -> i64;
+
+    /// Fuel spent by all the crabs moving to `pos`
+    fn total(&self, crabs: &Crabs, pos: i64) -> i64 {
+        crabs
+            .positions
+            .iter()
+            .map(|p| self.cost((p - pos).abs()))
+            .sum()
+    }
+}
+
+/// One unit per step
+pub struct Linear;
+
+/// Each step costs one more than the previous one
+pub struct Triangular;
+
+/// The square of the distance
+pub struct Quadratic;
+
+impl CostModel for Linear {
+    fn cost(&self, distance: i64) -> i64 {
+        distance
+    }
+
+    fn total(&self, crabs: &Crabs, pos: i64) -> i64 {
+        crabs.distances(pos)
+    }
+}
+
+impl CostModel for Triangular {
+    fn cost(&self, distance: i64) -> i64 {
+        distance * (distance + 1) / 2
+    }
+
+    // sum(d * (d + 1) / 2) = (sum(d^2) + sum(d)) / 2
+    fn total(&self, crabs: &Crabs, pos: i64) -> i64 {
+        (Quadratic.total(crabs, pos) + crabs.distances(pos)) / 2
+    }
+}
+
+impl CostModel for Quadratic {
+    fn cost(&self, distance: i64) -> i64 {
+        distance * distance
+    }
+
+    // sum((p - pos)^2) = sum(p^2) - 2 * pos * sum(p) + n * pos^2
+    fn total(&self, crabs: &Crabs, pos: i64) -> i64 {
+        let n = crabs.positions.len() as i64;
+        crabs.squares - 2 * pos * crabs.sum() + n * pos * pos
+    }
+}
+
+impl<F> CostModel for F
+where
+    F: Fn(i64) -> i64,
+{
+    fn cost(&self, distance: i64) -> i64 {
+        self(distance)
+    }
+}
+
+/// Best position to align the crabs, with the fuel spent
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Alignment {
+    pub position: i64,
+    pub fuel: i64,
+}
+
+/// Crab positions sorted with prefix sums, so the linear, triangular
+/// and quadratic fuel to any position takes O(log n) to compute
+#[derive(Debug, Clone)]
+pub struct Crabs {
+    positions: Vec<i64>,
+    prefix: Vec<i64>,
+    squares: i64,
+}
+
+impl Crabs {
+    pub fn new(input: &[i32]) -> Self {
+        let mut positions: Vec<i64> = input.iter().map(|p| *p as i64).collect();
+        positions.sort_unstable();
+        let prefix = std::iter::once(0)
+            .chain(positions.iter().scan(0, |acc, p| {
+                *acc += p;
+                Some(*acc)
+            }))
+            .collect();
+        let squares = positions.iter().map(|p| p * p).sum();
+        Self {
+            positions,
+            prefix,
+            squares,
         }
+    }
 
-        loop {
-            match decrease {
-                true => control -= 1,
-                false => control += 1,
-            }
-            let possible_optimal = expensive_fuel(input, control);
-            if possible_optimal >= current_optimal {
-                return Ok(current_optimal);
+    /// Total fuel to move every crab to `pos`
+    pub fn fuel<C: CostModel + ?Sized>(&self, pos: i64, cost: &C) -> i64 {
+        cost.total(self, pos)
+    }
+
+    /// Finds the position that spends the least fuel, the leftmost one on ties.
+    /// Being the cost convex, the total is too, so it binary searches
+    /// the first position where moving right stops saving fuel
+    pub fn align<C: CostModel + ?Sized>(&self, cost: &C) -> Result<Alignment> {
+        let (mut low, mut high) = match (self.positions.first(), self.positions.last()) {
+            (Some(first), Some(last)) => (*first, *last),
+            _ => bail!("no crabs to align"),
+        };
+        while low < high {
+            let mid = low + (high - low) / 2;
+            if self.fuel(mid + 1, cost) < self.fuel(mid, cost) {
+                low = mid + 1;
+            } else {
+                high = mid;
             }
-            current_optimal = possible_optimal;
+        }
+        Ok(Alignment {
+            position: low,
+            fuel: self.fuel(low, cost),
+        })
+    }
+
+    /// Fuel to move to every position between the first and the last crab
+    pub fn fuel_curve<C: CostModel + ?Sized>(&self, cost: &C) -> Vec<Alignment> {
+        match (self.positions.first(), self.positions.last()) {
+            (Some(first), Some(last)) => (*first..=*last)
+                .map(|position| Alignment {
+                    position,
+                    fuel: self.fuel(position, cost),
+                })
+                .collect(),
+            _ => Vec::new(),
         }
     }
+
+    /// Fuel curve with the linear and triangular costs as CSV
+    pub fn curve_to_csv(&self) -> String {
+        let linear = self.fuel_curve(&Linear);
+        let triangular = self.fuel_curve(&Triangular);
+        linear.iter().zip(triangular.iter()).fold(
+            String::from("position,linear,triangular\n"),
+            |mut csv, (l, t)| {
+                csv.push_str(&format!("{},{},{}\n", l.position, l.fuel, t.fuel));
+                csv
+            },
+        )
+    }
+
+    fn sum(&self) -> i64 {
+        self.prefix[self.positions.len()]
+    }
+
+    // Sum of the distances of every crab to `pos`
+    fn distances(&self, pos: i64) -> i64 {
+        let n = self.positions.len() as i64;
+        let k = self.positions.partition_point(|p| *p < pos);
+        let left = pos * k as i64 - self.prefix[k];
+        let right = (self.sum() - self.prefix[k]) - pos * (n - k as i64);
+        left + right
+    }
 }
 
-fn expensive_fuel(input: &[i32], pos: i32) -> i32 {
-    input.iter().fold(0, |acc, n| {
-        let diff = (pos - n).abs();
-        acc + (1..=diff).into_iter().sum::<i32>()
-    })
+const SPARK_LEVELS: &[u8] = b"_.-~=+*#%@";
+
+/// Draws the fuel curve in at most `width` characters, each one showing the
+/// lowest fuel of the positions it covers
+pub fn sparkline(curve: &[Alignment], width: usize) -> String {
+    let (min, max) = match (
+        curve.iter().map(|a| a.fuel).min(),
+        curve.iter().map(|a| a.fuel).max(),
+    ) {
+        (Some(min), Some(max)) => (min, max),
+        _ => return String::new(),
+    };
+    let chunk = curve.len().div_ceil(width.max(1));
+    curve
+        .chunks(chunk)
+        .map(|c| {
+            let fuel = c.iter().map(|a| a.fuel).min().unwrap_or(min);
+            let top = SPARK_LEVELS.len() as i64 - 1;
+            let level = match max - min {
+                0 => 0,
+                range => ((fuel - min) as i128 * top as i128 / range as i128) as usize,
+            };
+            SPARK_LEVELS[level] as char
+        })
+        .collect()
 }
 
 #[cfg(test)]
@@ -93,14 +244,130 @@ mod tests {
     }
 
     #[test]
-    fn test_expensive_fuel() -> Result<()> {
+    fn test_fuel() -> Result<()> {
+        let input = Day7::parse_input(INPUT)?;
+        let crabs = Crabs::new(&input);
+        assert_eq!(crabs.fuel(5, &Triangular), 168);
+        assert_eq!(crabs.fuel(2, &Triangular), 206);
+        assert_eq!(crabs.fuel(4, &Triangular), 170);
+        assert_eq!(crabs.fuel(2, &Linear), 37);
+        assert_eq!(crabs.fuel(1, &Linear), 41);
+        assert_eq!(crabs.fuel(3, &Linear), 39);
+        assert_eq!(crabs.fuel(10, &Linear), 71);
+        Ok(())
+    }
+
+    #[test]
+    fn test_align() -> Result<()> {
         let input = Day7::parse_input(INPUT)?;
-        let fuel = expensive_fuel(&input, 5);
-        assert_eq!(fuel, 168);
-        let fuel = expensive_fuel(&input, 2);
-        assert_eq!(fuel, 206);
-        let fuel = expensive_fuel(&input, 4);
-        assert_eq!(fuel, 170);
+        let crabs = Crabs::new(&input);
+        let alignment = crabs.align(&Linear)?;
+        assert_eq!(
+            alignment,
+            Alignment {
+                position: 2,
+                fuel: 37
+            }
+        );
+        let alignment = crabs.align(&Triangular)?;
+        assert_eq!(
+            alignment,
+            Alignment {
+                position: 5,
+                fuel: 168
+            }
+        );
+        assert!(Crabs::new(&[]).align(&Linear).is_err());
         Ok(())
     }
+
+    #[test]
+    fn test_align_brute_force() {
+        let input = vec![0, 0, 0, 0, 100, -7, 3, 3, 50];
+        let crabs = Crabs::new(&input);
+        let naive = |pos: i64, cost: &dyn Fn(i64) -> i64| -> i64 {
+            input.iter().map(|n| cost((pos - *n as i64).abs())).sum()
+        };
+        type Naive<'a> = &'a dyn Fn(i64) -> i64;
+        let models: Vec<(&dyn CostModel, Naive)> = vec![
+            (&Linear, &|d| d),
+            (&Triangular, &|d| (1..=d).sum()),
+            (&Quadratic, &|d| d * d),
+            (&|d: i64| d * d * d, &|d| d * d * d),
+        ];
+        for (model, cost) in models {
+            (-7..=100).for_each(|p| assert_eq!(crabs.fuel(p, model), naive(p, cost)));
+            let best = (-7..=100).map(|p| naive(p, cost)).min().unwrap();
+            assert_eq!(crabs.align(model).unwrap().fuel, best);
+        }
+    }
+
+    #[test]
+    fn test_align_custom() -> Result<()> {
+        let input = Day7::parse_input(INPUT)?;
+        let crabs = Crabs::new(&input);
+        let alignment = crabs.align(&Quadratic)?;
+        assert_eq!(
+            alignment,
+            Alignment {
+                position: 5,
+                fuel: 291
+            }
+        );
+        // Any convex closure works as a model
+        let alignment = crabs.align(&|d: i64| 3 * d)?;
+        assert_eq!(
+            alignment,
+            Alignment {
+                position: 2,
+                fuel: 111
+            }
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_fuel_curve() -> Result<()> {
+        let input = Day7::parse_input(INPUT)?;
+        let crabs = Crabs::new(&input);
+        let curve = crabs.fuel_curve(&Triangular);
+        assert_eq!(curve.len(), 17);
+        assert_eq!(
+            curve[0],
+            Alignment {
+                position: 0,
+                fuel: 290
+            }
+        );
+        assert_eq!(
+            curve[5],
+            Alignment {
+                position: 5,
+                fuel: 168
+            }
+        );
+        let best = curve.iter().min_by_key(|a| a.fuel).unwrap();
+        assert_eq!(*best, crabs.align(&Triangular)?);
+        assert!(Crabs::new(&[]).fuel_curve(&Linear).is_empty());
+        Ok(())
+    }
+
+    #[test]
+    fn test_curve_to_csv() -> Result<()> {
+        let crabs = Crabs::new(&[1, 3]);
+        let csv = crabs.curve_to_csv();
+        assert_eq!(csv, "position,linear,triangular\n1,2,3\n2,2,2\n3,2,3\n");
+        Ok(())
+    }
+
+    #[test]
+    fn test_sparkline() {
+        let crabs = Crabs::new(&[0, 8]);
+        let curve = crabs.fuel_curve(&Quadratic);
+        assert_eq!(sparkline(&curve, 9), "@+-___-+@");
+        assert_eq!(sparkline(&curve, 3), "-_-");
+        let flat = crabs.fuel_curve(&Linear);
+        assert_eq!(sparkline(&flat, 100), "_________");
+        assert_eq!(sparkline(&[], 10), "");
+    }
 }
@@ -1,28 +1,42 @@
-use std::collections::HashMap;
+use std::fmt::Display;
+use std::ops::{BitAnd, BitOr, Not};
+use std::str::FromStr;
 
-use anyhow::Result;
+use anyhow::{Error, Result};
 
-use crate::runner::{ParseWithLifeTime, Run};
+use crate::runner::{Parse, Run};
 
 pub struct Day8 {}
 
-impl<'a> ParseWithLifeTime<'a, Vec<Metric<'a>>> for Day8 {
-    fn parse_input(input: &'a str) -> Result<Vec<Metric<'a>>> {
-        let r = input
+impl Parse<Vec<Metric>> for Day8 {
+    fn parse_input(input: &str) -> Result<Vec<Metric>> {
+        input
             .lines()
-            .filter_map(|l| l.split_once(" | "))
-            .map(|(input, output)| Metric {
-                input: input.split_whitespace().collect(),
-                output: output.split_whitespace().collect(),
+            .enumerate()
+            .filter(|(_, l)| !l.trim().is_empty())
+            .map(|(i, l)| {
+                let (input, output) = l
+                    .split_once(" | ")
+                    .ok_or(anyhow::anyhow!("line {}: missing \" | \"", i + 1))?;
+                Ok(Metric {
+                    line: i + 1,
+                    input: input
+                        .split_whitespace()
+                        .map(str::parse)
+                        .collect::<Result<_>>()?,
+                    output: output
+                        .split_whitespace()
+                        .map(str::parse)
+                        .collect::<Result<_>>()?,
+                })
             })
-            .collect();
-        Ok(r)
+            .collect()
     }
 }
 
-impl Run<Vec<Metric<'_>>, usize> for Day8 {
-    fn part_one(input: &Vec<Metric<'_>>) -> Result<usize> {
-        let known_lenghts: Vec<usize> = vec![2, 3, 4, 7];
+impl Run<Vec<Metric>, usize> for Day8 {
+    fn part_one(input: &Vec<Metric>) -> Result<usize> {
+        let known_lenghts: Vec<u32> = vec![2, 3, 4, 7];
         let count = input.iter().fold(0, |acc, m| {
             let sum = m
                 .output
@@ -34,120 +48,341 @@ impl Run<Vec<Metric<'_>>, usize> for Day8 {
         Ok(count)
     }
 
-    fn part_two(input: &Vec<Metric<'_>>) -> Result<usize> {
-        let r = input
-            .iter()
-            .filter_map(|m| Some((find_codex_on_input(&m.input)?, &m.output)))
-            .flat_map(|(codex, output)| {
-                output
-                    .iter()
-                    .filter_map(|o| {
-                        codex
-                            .iter()
-                            .find(|(k, _)| contains_all(k, o) && k.len() == o.len())
-                    })
-                    .fold(String::new(), |mut value, (_, v)| {
-                        value.push(*v);
-                        value
-                    })
-                    .parse::<usize>()
+    fn part_two(input: &Vec<Metric>) -> Result<usize> {
+        sum_outputs(input, Mode::Lenient)
+    }
+}
+
+/// What to do with the entries that can't be decoded
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Mode {
+    /// Leave them out of the sum
+    Lenient,
+    /// Fail with the first one
+    Strict,
+}
+
+/// Value shown by the output of the entry
+pub fn decode_metric(metric: &Metric) -> std::result::Result<usize, DecodeError> {
+    let wiring = find_wiring(&metric.input)?;
+    metric.output.iter().try_fold(0, |value, o| {
+        let digit = wiring.decode(*o).ok_or(DecodeError::UnknownDigit(*o))?;
+        Ok(value * 10 + digit as usize)
+    })
+}
+
+/// Decodes every entry, with the line it comes from
+pub fn decode_entries(input: &[Metric]) -> Vec<(usize, std::result::Result<usize, DecodeError>)> {
+    input.iter().map(|m| (m.line, decode_metric(m))).collect()
+}
+
+/// Sum of the output values of all the entries
+pub fn sum_outputs(input: &[Metric], mode: Mode) -> Result<usize> {
+    decode_entries(input)
+        .into_iter()
+        .try_fold(0, |sum, (line, value)| match (value, mode) {
+            (Ok(value), _) => Ok(sum + value),
+            (Err(_), Mode::Lenient) => Ok(sum),
+            (Err(e), Mode::Strict) => Err(anyhow::anyhow!("line {}: {}", line, e)),
+        })
+}
+
+/// Set of wires or segments, bit 0 is 'a' and bit 6 is 'g'.
+/// The same letters in any order give the same pattern
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub struct Pattern(u8);
+
+impl Pattern {
+    pub const ALL: Pattern = Pattern(0b1111111);
+
+    pub fn union(self, other: Pattern) -> Pattern {
+        Pattern(self.0 | other.0)
+    }
+
+    pub fn intersection(self, other: Pattern) -> Pattern {
+        Pattern(self.0 & other.0)
+    }
+
+    /// Number of wires or segments on
+    pub fn len(self) -> u32 {
+        self.0.count_ones()
+    }
+
+    pub fn contains(self, index: u8) -> bool {
+        self.0 & 1 << index != 0
+    }
+
+    /// Indexes of the wires or segments on, from 'a'
+    pub fn iter(self) -> impl Iterator<Item = u8> {
+        (0..7).filter(move |i| self.contains(*i))
+    }
+}
+
+impl BitOr for Pattern {
+    type Output = Pattern;
+
+    fn bitor(self, rhs: Pattern) -> Pattern {
+        self.union(rhs)
+    }
+}
+
+impl BitAnd for Pattern {
+    type Output = Pattern;
+
+    fn bitand(self, rhs: Pattern) -> Pattern {
+        self.intersection(rhs)
+    }
+}
+
+impl Not for Pattern {
+    type Output = Pattern;
+
+    fn not(self) -> Pattern {
+        Pattern(!self.0 & Pattern::ALL.0)
+    }
+}
+
+impl FromStr for Pattern {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        s.chars()
+            .try_fold(Pattern::default(), |pattern, c| match c {
+                'a'..='g' => Ok(pattern | Pattern(1 << (c as u8 - b'a'))),
+                _ => Err(anyhow::anyhow!("invalid wire {:?} on {:?}", c, s)),
             })
-            .sum();
-        Ok(r)
-    }
-}
-
-// TODO: Find if there is a way to do this on the executor
-impl Day8 {
-    pub fn run(path: &str) -> Result<(usize, usize)> {
-        let input = std::fs::read_to_string(path)?;
-        let input = Self::parse_input(input.as_str())?;
-        let r1 = Self::part_one(&input)?;
-        println!("{} part 1: {}", path, r1);
-        let r2 = Self::part_two(&input)?;
-        println!("{} part 2: {}", path, r2);
-        Ok((r1, r2))
-    }
-}
-
-// FIXME: This is hardcoded brute force... gotta be a better way
-fn find_codex_on_input<'a>(input: &'a [&str]) -> Option<HashMap<&'a str, char>> {
-    let known_index = HashMap::from([(2, '1'), (4, '4'), (3, '7'), (7, '8')]);
-    let mut codex = HashMap::with_capacity(10);
-    let mut four = "";
-    let mut group_six = Vec::new();
-    let mut group_five = Vec::new();
-    for entry in input {
-        let len = entry.len();
-        match len {
-            4 => {
-                codex.insert(*entry, '4');
-                four = *entry;
-            }
-            5 => {
-                group_five.push(*entry);
-            }
-            6 => {
-                group_six.push(*entry);
-            }
-            _ => {
-                codex.insert(*entry, known_index[&len]);
-            }
+    }
+}
+
+impl Display for Pattern {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let letters: String = self.iter().map(|i| (b'a' + i) as char).collect();
+        write!(f, "{}", letters)
+    }
+}
+
+// Segments lit for each digit
+const DIGITS: [Pattern; 10] = [
+    Pattern(0b1110111),
+    Pattern(0b0100100),
+    Pattern(0b1011101),
+    Pattern(0b1101101),
+    Pattern(0b0101110),
+    Pattern(0b1101011),
+    Pattern(0b1111011),
+    Pattern(0b0100101),
+    Pattern(0b1111111),
+    Pattern(0b1101111),
+];
+
+/// Segment each wire is connected to, both as indexes from 'a'
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Wiring([u8; 7]);
+
+impl Wiring {
+    /// Segment lit by the wire
+    pub fn segment(&self, wire: char) -> Option<char> {
+        let index = (wire as usize).checked_sub('a' as usize)?;
+        let segment = self.0.get(index)?;
+        Some((b'a' + segment) as char)
+    }
+
+    /// Digit shown by the pattern of wires
+    pub fn decode(&self, pattern: Pattern) -> Option<u8> {
+        let segments = self.segments(pattern);
+        DIGITS.iter().position(|d| *d == segments).map(|d| d as u8)
+    }
+
+    /// Segments lit by the pattern of wires
+    pub fn segments(&self, pattern: Pattern) -> Pattern {
+        pattern.iter().fold(Pattern::default(), |mask, w| {
+            mask | Pattern(1 << self.0[w as usize])
+        })
+    }
+}
+
+impl Display for Wiring {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let mapping: Vec<String> = ('a'..='g')
+            .filter_map(|w| Some(format!("{}->{}", w, self.segment(w)?)))
+            .collect();
+        write!(f, "{}", mapping.join(" "))
+    }
+}
+
+/// Why the wiring of an entry can't be found
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DecodeError {
+    /// No wiring shows every pattern as a different digit
+    Contradiction,
+    /// More than one wiring matches the patterns, the search stops at the
+    /// first two found
+    Ambiguous(Vec<Wiring>),
+    /// An output pattern isn't any digit with the wiring found
+    UnknownDigit(Pattern),
+}
+
+impl Display for DecodeError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            DecodeError::Contradiction => write!(f, "no wiring matches the patterns"),
+            DecodeError::Ambiguous(_) => write!(f, "at least two wirings match the patterns"),
+            DecodeError::UnknownDigit(p) => write!(f, "output {} is not a digit", p),
         }
     }
-    // Nine is the only one on grop six that contains all 4
-    let index = group_six.iter().position(|e| contains_all(e, four))?;
-    let number = group_six.swap_remove(index);
-    codex.insert(number, '9');
+}
 
-    // two is the only one on grop five that contains nine but for one
-    let index = group_five
-        .iter()
-        .position(|e| contains_but_one(e, number))?;
-    let number = group_five.swap_remove(index);
-    codex.insert(number, '2');
+impl std::error::Error for DecodeError {}
 
-    // three is the only one on group five that contains two but for one
-    let index = group_five
-        .iter()
-        .position(|e| contains_but_one(e, number))?;
-    let number = group_five.swap_remove(index);
-    codex.insert(number, '3');
-    // five is the remaining one on the group
-    let number = group_five.pop()?;
-    codex.insert(number, '5');
+/// Finds how the wires are connected to the segments, narrowing the segments
+/// each wire can be connected to by the length of the patterns, then searching
+/// the permutations that are left
+pub fn find_wiring(input: &[Pattern]) -> std::result::Result<Wiring, DecodeError> {
+    // Segments each wire can still be connected to
+    let mut candidates = [Pattern::ALL; 7];
+    for pattern in input {
+        let same_len = DIGITS.iter().filter(|d| d.len() == pattern.len());
+        // Segments lit in any and in all of the digits this pattern can be
+        let any = same_len.clone().fold(Pattern::default(), |acc, d| acc | *d);
+        let all = same_len.fold(Pattern::ALL, |acc, d| acc & *d);
+        for (w, candidate) in candidates.iter_mut().enumerate() {
+            match pattern.contains(w as u8) {
+                true => *candidate = *candidate & any,
+                false => *candidate = *candidate & !all,
+            }
+        }
+    }
 
-    // six contains all 5
-    let index = group_six.iter().position(|e| contains_all(e, number))?;
-    let number = group_six.swap_remove(index);
-    codex.insert(number, '6');
-    // zero is the remaining one on the group
-    let number = group_six.pop()?;
-    codex.insert(number, '0');
+    let mut found = Vec::new();
+    search_wiring(
+        input,
+        &candidates,
+        &mut [0; 7],
+        0,
+        Pattern::default(),
+        &mut found,
+    );
+    match found.len() {
+        0 => Err(DecodeError::Contradiction),
+        1 => Ok(found[0]),
+        _ => Err(DecodeError::Ambiguous(found)),
+    }
+}
 
-    Some(codex)
+// Backtracks over the candidates assigning a segment to each wire,
+// stopping once it finds a second wiring
+fn search_wiring(
+    input: &[Pattern],
+    candidates: &[Pattern; 7],
+    wiring: &mut [u8; 7],
+    wire: usize,
+    used: Pattern,
+    found: &mut Vec<Wiring>,
+) {
+    if found.len() > 1 {
+        return;
+    }
+    if wire == 7 {
+        let wiring = Wiring(*wiring);
+        let digits: Option<Vec<u8>> = input.iter().map(|p| wiring.decode(*p)).collect();
+        // Every pattern has to be a digit, and all of them different when there are ten
+        let valid = digits.is_some_and(|d| {
+            let seen = d.iter().fold(0u16, |seen, d| seen | 1 << d);
+            input.len() < 10 || seen == 0b1111111111
+        });
+        if valid {
+            found.push(wiring);
+        }
+        return;
+    }
+    for segment in (candidates[wire] & !used).iter() {
+        wiring[wire] = segment;
+        search_wiring(
+            input,
+            candidates,
+            wiring,
+            wire + 1,
+            used | Pattern(1 << segment),
+            found,
+        );
+    }
 }
 
-fn contains_but_one(a: &str, b: &str) -> bool {
-    a.chars().filter(|c| !b.contains(*c)).count() == 1
+// Where each segment is drawn, as (row, column, character)
+const SEGMENT_CELLS: [(usize, usize, char); 7] = [
+    (0, 1, '_'),
+    (1, 0, '|'),
+    (1, 2, '|'),
+    (1, 1, '_'),
+    (2, 0, '|'),
+    (2, 2, '|'),
+    (2, 1, '_'),
+];
+
+/// Draws the patterns side by side as seven segment displays, three lines high
+pub fn render_patterns(patterns: &[Pattern]) -> String {
+    let mut rows = vec![String::new(); 3];
+    for pattern in patterns {
+        let mut cells = [[' '; 3]; 3];
+        pattern.iter().for_each(|s| {
+            let (row, column, c) = SEGMENT_CELLS[s as usize];
+            cells[row][column] = c;
+        });
+        rows.iter_mut().zip(cells.iter()).for_each(|(row, cells)| {
+            row.extend(cells.iter());
+            row.push(' ');
+        });
+    }
+    rows.iter().fold(String::new(), |mut display, row| {
+        display.push_str(row.trim_end());
+        display.push('\n');
+        display
+    })
 }
 
-fn contains_all(a: &str, b: &str) -> bool {
-    a.chars().filter(|c| b.contains(*c)).count() == b.len()
+/// Draws the output of the entry as wired, taking each wire as the segment
+/// with its letter, and corrected with the wiring found for the entry
+pub fn render_metric(metric: &Metric) -> String {
+    let mut display = format!("wired:\n{}", render_patterns(&metric.output));
+    match find_wiring(&metric.input) {
+        Ok(wiring) => {
+            let segments: Vec<Pattern> =
+                metric.output.iter().map(|o| wiring.segments(*o)).collect();
+            let value: String = metric
+                .output
+                .iter()
+                .map(|o| match wiring.decode(*o) {
+                    Some(d) => (b'0' + d) as char,
+                    None => '?',
+                })
+                .collect();
+            display.push_str(&format!("corrected {} ({}):\n", value, wiring));
+            display.push_str(&render_patterns(&segments));
+        }
+        Err(e) => display.push_str(&format!("not decoded: {}\n", e)),
+    }
+    display
 }
 
-#[derive(Debug)]
-pub struct Metric<'a> {
-    input: Vec<&'a str>,
-    output: Vec<&'a str>,
+/// An entry of the notes, the ten unique patterns and the four output ones
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Metric {
+    line: usize,
+    input: Vec<Pattern>,
+    output: Vec<Pattern>,
 }
 
 #[cfg(test)]
 mod tests_day8 {
     use super::*;
+    use crate::runner::Executor;
 
     const INPUT: &str = include_str!("../inputs/day8.test");
 
+    fn patterns(input: &[&str]) -> Vec<Pattern> {
+        input.iter().map(|p| p.parse().unwrap()).collect()
+    }
+
     #[test]
     fn test_part_one() -> Result<()> {
         let input = Day8::parse_input(INPUT)?;
@@ -164,18 +399,146 @@ mod tests_day8 {
         Ok(())
     }
 
+    #[test]
+    fn test_run() -> Result<()> {
+        let (r1, r2) = Day8::run("inputs/day8.test")?;
+        assert_eq!(r1, 26);
+        assert_eq!(r2, 61229);
+        Ok(())
+    }
+
+    #[test]
+    fn test_metrics_across_threads() -> Result<()> {
+        let input = Day8::parse_input(INPUT)?;
+        let handle = std::thread::spawn(move || Day8::part_two(&input));
+        let count = handle.join().expect("thread panicked")?;
+        assert_eq!(count, 61229);
+        Ok(())
+    }
+
+    #[test]
+    fn test_render_patterns() {
+        let display = render_patterns(&patterns(&["abcefg", "cf", "acdeg", "abdfg"]));
+        assert_eq!(
+            display,
+            " _       _   _\n\
+             | |   |  _| |_\n\
+             |_|   | |_   _|\n"
+        );
+    }
+
+    #[test]
+    fn test_render_metric() -> Result<()> {
+        let input = Day8::parse_input(
+            "acedgfb cdfbe gcdfa fbcad dab cefabd cdfgeb eafb cagedb ab | cdfeb fcadb cdfeb cdbaf",
+        )?;
+        let display = render_metric(&input[0]);
+        let mut lines = display.lines();
+        assert_eq!(lines.next(), Some("wired:"));
+        assert_eq!(
+            lines.nth(3),
+            Some("corrected 5353 (a->c b->f c->g d->a e->b f->d g->e):")
+        );
+        assert_eq!(lines.next(), Some(" _   _   _   _"));
+        assert_eq!(lines.next(), Some("|_   _| |_   _|"));
+        assert_eq!(lines.next(), Some(" _|  _|  _|  _|"));
+
+        let input = Day8::parse_input("ab cd | ab ab ab ab")?;
+        let display = render_metric(&input[0]);
+        assert!(display.ends_with("not decoded: no wiring matches the patterns\n"));
+        Ok(())
+    }
+
+    #[test]
+    fn test_decode_entries() -> Result<()> {
+        let input = Day8::parse_input(
+            "acedgfb cdfbe gcdfa fbcad dab cefabd cdfgeb eafb cagedb ab | cdfeb fcadb cdfeb cdbaf
+
+             ab cd | ab ab ab ab
+             acedgfb cdfbe gcdfa fbcad dab cefabd cdfgeb eafb cagedb ab | cdfeb abc cdfeb cdbaf",
+        )?;
+        let entries = decode_entries(&input);
+        assert_eq!(entries.len(), 3);
+        assert_eq!(entries[0], (1, Ok(5353)));
+        assert_eq!(entries[1], (3, Err(DecodeError::Contradiction)));
+        assert_eq!(
+            entries[2],
+            (4, Err(DecodeError::UnknownDigit("abc".parse()?)))
+        );
+        assert_eq!(sum_outputs(&input, Mode::Lenient)?, 5353);
+        let error = sum_outputs(&input, Mode::Strict).unwrap_err();
+        assert_eq!(error.to_string(), "line 3: no wiring matches the patterns");
+        assert_eq!(sum_outputs(&input[..1], Mode::Strict)?, 5353);
+        assert!(Day8::parse_input("ab cd ab ab ab ab").is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_pattern() -> Result<()> {
+        let ab: Pattern = "ba".parse()?;
+        assert_eq!(ab, "ab".parse()?);
+        assert_eq!(ab.len(), 2);
+        let bcd: Pattern = "dcb".parse()?;
+        assert_eq!(ab | bcd, "abcd".parse()?);
+        assert_eq!(ab & bcd, "b".parse()?);
+        assert_eq!(ab.union(bcd).len(), 4);
+        assert_eq!(ab.intersection("cd".parse()?), Pattern::default());
+        assert_eq!(!ab, "cdefg".parse()?);
+        assert_eq!(bcd.to_string(), "bcd");
+        assert!("abx".parse::<Pattern>().is_err());
+        assert!(Day8::parse_input("ab cdh | ab ab ab ab").is_err());
+        Ok(())
+    }
+
     #[test]
     fn test_codex() {
-        let input = vec![
+        let input = patterns(&[
             "acedgfb", "cdfbe", "gcdfa", "fbcad", "dab", "cefabd", "cdfgeb", "eafb", "cagedb", "ab",
-        ];
-
-        let codex = find_codex_on_input(&input);
-        assert!(codex.is_some());
-        let codex = codex.unwrap();
-        assert_eq!(codex[&"cagedb"], '0');
-        assert_eq!(codex[&"ab"], '1');
-        assert_eq!(codex[&"acedgfb"], '8');
-        assert_eq!(codex[&"cdfbe"], '5');
+        ]);
+
+        let wiring = find_wiring(&input);
+        assert!(wiring.is_ok());
+        let wiring = wiring.unwrap();
+        let decode = |p: &str| wiring.decode(p.parse().unwrap());
+        assert_eq!(decode("cagedb"), Some(0));
+        assert_eq!(decode("ab"), Some(1));
+        assert_eq!(decode("acedgfb"), Some(8));
+        assert_eq!(decode("cdfbe"), Some(5));
+        // Letter order doesn't matter
+        assert_eq!(decode("ecbdf"), Some(5));
+        assert_eq!(decode("abc"), None);
+    }
+
+    #[test]
+    fn test_find_wiring() {
+        let input = patterns(&[
+            "acedgfb", "cdfbe", "gcdfa", "fbcad", "dab", "cefabd", "cdfgeb", "eafb", "cagedb", "ab",
+        ]);
+        let wiring = find_wiring(&input).unwrap();
+        let mapping: String = ('a'..='g').filter_map(|w| wiring.segment(w)).collect();
+        assert_eq!(mapping, "cfgabde");
+        assert_eq!(wiring.to_string(), "a->c b->f c->g d->a e->b f->d g->e");
+        assert_eq!(wiring.segments("dab".parse().unwrap()).to_string(), "acf");
+    }
+
+    #[test]
+    fn test_find_wiring_errors() {
+        // Unscrambled, but 7 and 8 shown by the same wires as 1
+        let input = patterns(&[
+            "abcefg", "cf", "acdeg", "acdfg", "bcdf", "abdfg", "abdefg", "cf", "cf", "abcdfg",
+        ]);
+        assert_eq!(find_wiring(&input), Err(DecodeError::Contradiction));
+        // Only a 1 and an 8, the other five wires can be swapped
+        let input = patterns(&["ab", "abcdefg"]);
+        match find_wiring(&input) {
+            Err(DecodeError::Ambiguous(w)) => {
+                assert_eq!(w.len(), 2);
+                assert_eq!(
+                    DecodeError::Ambiguous(w).to_string(),
+                    "at least two wirings match the patterns"
+                );
+            }
+            r => panic!("expected ambiguous, got {:?}", r),
+        }
     }
 }
@@ -1,136 +1,331 @@
-use std::collections::{HashMap, HashSet};
+use std::collections::VecDeque;
 
 use anyhow::Result;
 
+use crate::grid::{Connectivity, Grid, Pos};
 use crate::runner::{Parse, Run};
 
 pub struct Day9 {}
 
-impl Parse<Vec<Vec<u32>>> for Day9 {
-    fn parse_input(input: &str) -> Result<Vec<Vec<u32>>> {
-        let r = input
-            .lines()
-            .map(|s| {
-                s.chars()
-                    .into_iter()
-                    .filter_map(|c| c.to_digit(10))
-                    .collect()
-            })
-            .collect();
-        Ok(r)
+impl Parse<Grid<u32>> for Day9 {
+    fn parse_input(input: &str) -> Result<Grid<u32>> {
+        Grid::parse_digits(input)
     }
 }
 
-impl Run<Vec<Vec<u32>>, u32> for Day9 {
-    fn part_one(input: &Vec<Vec<u32>>) -> Result<u32> {
-        let sum = input
-            .iter()
-            .enumerate()
-            .map(|(i, line)| {
-                line.iter()
-                    .enumerate()
-                    .filter(|(j, n)| is_lowest_adjacent(*n, i, *j, input))
-                    .map(|(_, n)| n + 1)
-                    .sum::<u32>()
-            })
-            .sum();
-        Ok(sum)
+impl Run<Grid<u32>, u32> for Day9 {
+    fn part_one(input: &Grid<u32>) -> Result<u32> {
+        Ok(risk_level(input, &BasinConfig::default()))
+    }
+
+    fn part_two(input: &Grid<u32>) -> Result<u32> {
+        Ok(basin_product(input, &BasinConfig::default()))
+    }
+}
+
+/// Sum of the height plus one of the low areas, a plateau counts once
+pub fn risk_level(map: &Grid<u32>, config: &BasinConfig) -> u32 {
+    low_areas(map, config)
+        .iter()
+        .map(|area| map[area[0]] + 1)
+        .sum()
+}
+
+/// Product of the sizes of the three largest basins
+pub fn basin_product(map: &Grid<u32>, config: &BasinConfig) -> u32 {
+    let basins = label_basins(map, config);
+    largest_basins(&basins, 3)
+        .into_iter()
+        .map(|label| basins.basins[label].size() as u32)
+        .product()
+}
+
+/// Cells that flow down to a low point
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Basin {
+    /// First cell of the low area
+    pub low_point: Pos,
+    /// Cells at the bottom of the basin, more than one when it's a plateau
+    pub low_area: Vec<Pos>,
+    pub cells: Vec<Pos>,
+}
+
+impl Basin {
+    pub fn size(&self) -> usize {
+        self.cells.len()
+    }
+}
+
+/// Every basin of the map, with the index of the basin each cell belongs to.
+/// Walls and cells not reached from a low point have no label
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Basins {
+    pub labels: Grid<Option<usize>>,
+    pub basins: Vec<Basin>,
+}
+
+/// Which cells make a basin
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BasinRule {
+    /// The cells reached from the low point without crossing a wall
+    Walls,
+    /// The cells that only flow down to the low point
+    Flow,
+}
+
+/// How the heightmap is split in basins
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BasinConfig {
+    /// Cells this high or higher aren't part of any basin
+    pub wall: u32,
+    pub connectivity: Connectivity,
+    pub rule: BasinRule,
+    /// Take connected cells of the same height, with only higher cells
+    /// around them, as a single low area instead of having no low point
+    pub plateaus: bool,
+}
+
+impl Default for BasinConfig {
+    fn default() -> Self {
+        Self {
+            wall: 9,
+            connectivity: Connectivity::Four,
+            rule: BasinRule::Walls,
+            plateaus: false,
+        }
     }
+}
+
+/// Low points a cell flows down to, following any lower neighbour
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Drain {
+    /// It doesn't flow to any low point, like on a plateau
+    Nowhere,
+    To(Pos),
+    /// It flows to more than one low point
+    Many,
+}
+
+impl Drain {
+    fn merge(self, other: Drain) -> Drain {
+        match (self, other) {
+            (Drain::Nowhere, d) | (d, Drain::Nowhere) => d,
+            (Drain::To(a), Drain::To(b)) if a == b => Drain::To(a),
+            _ => Drain::Many,
+        }
+    }
+}
 
-    fn part_two(input: &Vec<Vec<u32>>) -> Result<u32> {
-        let mut basins: Vec<u32> = input
+/// Low areas that aren't walls, by their first cell row by row.
+/// Without plateaus each one is a single low point
+pub fn low_areas(map: &Grid<u32>, config: &BasinConfig) -> Vec<Vec<Pos>> {
+    if !config.plateaus {
+        return map
             .iter()
-            .enumerate()
-            .flat_map(move |(i, line)| {
-                line.iter()
-                    .enumerate()
-                    .filter(move |(j, n)| is_lowest_adjacent(*n, i, *j, input))
-                    .map(move |(j, _)| basin_size(i, j, input))
-            })
+            .filter(|(pos, n)| **n < config.wall && is_low_point(map, *pos, config.connectivity))
+            .map(|(pos, _)| vec![pos])
             .collect();
-        basins.sort_unstable();
-        let r: u32 = basins.into_iter().rev().take(3).product();
+    }
+    let mut visited = Grid::filled(map.width(), map.height(), false);
+    let mut areas = Vec::new();
+    for (start, height) in map.iter() {
+        if *height >= config.wall || visited[start] {
+            continue;
+        }
+        // Fill the cells of the same height, checking nothing around is lower
+        let mut area = Vec::new();
+        let mut is_low = true;
+        let mut queue = VecDeque::from([start]);
+        visited[start] = true;
+        while let Some(pos) = queue.pop_front() {
+            area.push(pos);
+            for next in map.neighbours(pos, config.connectivity) {
+                if map[next] < *height {
+                    is_low = false;
+                } else if map[next] == *height && !visited[next] {
+                    visited[next] = true;
+                    queue.push_back(next);
+                }
+            }
+        }
+        if is_low {
+            areas.push(area);
+        }
+    }
+    areas
+}
 
-        Ok(r)
+/// Where each cell drains to, `None` for the walls.
+/// Cells are visited from the lowest, so their lower neighbours are already known
+pub fn drainage(map: &Grid<u32>, config: &BasinConfig) -> Grid<Option<Drain>> {
+    let mut drains = Grid::filled(map.width(), map.height(), None);
+    // Cells of the low areas drain to the first cell of their area
+    let mut bottoms = Grid::filled(map.width(), map.height(), None);
+    for area in low_areas(map, config) {
+        area.iter().for_each(|pos| bottoms[*pos] = Some(area[0]));
+    }
+    let mut cells: Vec<Pos> = map
+        .iter()
+        .filter(|(_, n)| **n < config.wall)
+        .map(|(pos, _)| pos)
+        .collect();
+    cells.sort_by_key(|pos| map[*pos]);
+    for pos in cells {
+        let drain = match bottoms[pos] {
+            Some(bottom) => Drain::To(bottom),
+            None => map
+                .neighbours(pos, config.connectivity)
+                .filter(|next| map[*next] < map[pos])
+                .filter_map(|next| drains[next])
+                .fold(Drain::Nowhere, Drain::merge),
+        };
+        drains[pos] = Some(drain);
     }
+    drains
 }
 
-fn basin_size(i: usize, j: usize, map: &[Vec<u32>]) -> u32 {
-    let mut visited = HashSet::from([(i, j)]);
-    let adjacents = create_adjacents(i, j, map);
-    adjacents
-        .into_iter()
-        .filter(|(_, a)| *a < 9)
-        .fold(1, |acc, (k, _)| {
-            acc + count_neightbors(k.0, k.1, map, &mut visited)
+/// Cells that break the assumption of every cell that isn't a wall
+/// flowing down to exactly one low point
+pub fn drainage_violations(map: &Grid<u32>, config: &BasinConfig) -> Vec<(Pos, Drain)> {
+    drainage(map, config)
+        .iter()
+        .filter_map(|(pos, drain)| match drain {
+            Some(Drain::To(_)) | None => None,
+            Some(drain) => Some((pos, *drain)),
         })
+        .collect()
 }
 
-fn count_neightbors(
-    i: usize,
-    j: usize,
-    map: &[Vec<u32>],
-    visited: &mut HashSet<(usize, usize)>,
-) -> u32 {
-    if !visited.insert((i, j)) {
-        return 0;
+/// Splits the map in a basin per low point, filling them breadth first
+/// so big maps don't overflow the stack
+pub fn label_basins(map: &Grid<u32>, config: &BasinConfig) -> Basins {
+    let mut labels = Grid::filled(map.width(), map.height(), None);
+    let mut basins = Vec::new();
+    match config.rule {
+        BasinRule::Walls => {
+            for low_area in low_areas(map, config) {
+                let low_point = low_area[0];
+                // Already filled from another low point of the same basin
+                if labels[low_point].is_some() {
+                    continue;
+                }
+                let label = basins.len();
+                let mut cells = Vec::new();
+                let mut queue = VecDeque::from(low_area.clone());
+                low_area.iter().for_each(|pos| labels[*pos] = Some(label));
+                while let Some(pos) = queue.pop_front() {
+                    cells.push(pos);
+                    for next in map.neighbours(pos, config.connectivity) {
+                        if map[next] < config.wall && labels[next].is_none() {
+                            labels[next] = Some(label);
+                            queue.push_back(next);
+                        }
+                    }
+                }
+                basins.push(Basin {
+                    low_point,
+                    low_area,
+                    cells,
+                });
+            }
+        }
+        BasinRule::Flow => {
+            let drains = drainage(map, config);
+            for (label, low_area) in low_areas(map, config).into_iter().enumerate() {
+                labels[low_area[0]] = Some(label);
+                basins.push(Basin {
+                    low_point: low_area[0],
+                    low_area,
+                    cells: Vec::new(),
+                });
+            }
+            for (pos, drain) in drains.iter() {
+                if let Some(Drain::To(low_point)) = drain {
+                    let label = labels[*low_point];
+                    labels[pos] = label;
+                    if let Some(label) = label {
+                        basins[label].cells.push(pos);
+                    }
+                }
+            }
+        }
     }
-    let adjacents = create_adjacents(i, j, map);
-    adjacents
-        .into_iter()
-        .filter(|(_, a)| *a < 9)
-        .fold(1, |acc, (k, _)| {
-            acc + count_neightbors(k.0, k.1, map, visited)
-        })
+    Basins { labels, basins }
 }
 
-fn is_lowest_adjacent(value: &u32, i: usize, j: usize, map: &[Vec<u32>]) -> bool {
-    let adjacents = create_adjacents(i, j, map);
-    adjacents.values().filter(|a| a <= &value).count() == 0
+/// Labels of the `n` biggest basins, from the biggest
+pub fn largest_basins(basins: &Basins, n: usize) -> Vec<usize> {
+    let mut labels: Vec<usize> = (0..basins.basins.len()).collect();
+    labels.sort_by_key(|label| std::cmp::Reverse(basins.basins[*label].size()));
+    labels.truncate(n);
+    labels
 }
 
-// TODO: Simplify coordinates
-fn create_adjacents(i: usize, j: usize, map: &[Vec<u32>]) -> HashMap<(usize, usize), u32> {
-    let i_len = map.len() - 1;
-    let j_len = map[i].len() - 1;
-    match (i, j) {
-        (0, 0) => HashMap::from([((i + 1, j), map[i + 1][j]), ((i, j + 1), map[i][j + 1])]),
-        (0, j) if j == j_len => {
-            HashMap::from([((i + 1, j), map[i + 1][j]), ((i, j - 1), map[i][j - 1])])
-        }
-        (i, 0) if i == i_len => {
-            HashMap::from([((i - 1, j), map[i - 1][j]), ((i, j + 1), map[i][j + 1])])
-        }
-        (i, j) if i == i_len && j == j_len => {
-            HashMap::from([((i - 1, j), map[i - 1][j]), ((i, j - 1), map[i][j - 1])])
+// ANSI foreground colours for the basins, and RGB ones for the image
+const ANSI_COLOURS: [u8; 6] = [31, 32, 33, 34, 35, 36];
+const RGB_COLOURS: [[u8; 3]; 6] = [
+    [220, 50, 47],
+    [133, 153, 0],
+    [181, 137, 0],
+    [38, 139, 210],
+    [211, 54, 130],
+    [42, 161, 152],
+];
+
+/// Draws the heightmap with a colour per basin, the three largest ones in bold,
+/// the low points in reverse video and the walls in grey
+pub fn render_ansi(map: &Grid<u32>, basins: &Basins) -> String {
+    let largest = largest_basins(basins, 3);
+    let mut display = String::new();
+    for (pos, height) in map.iter() {
+        let style = match basins.labels[pos] {
+            Some(label) => {
+                let colour = ANSI_COLOURS[label % ANSI_COLOURS.len()];
+                let bold = if largest.contains(&label) { ";1" } else { "" };
+                let low = if basins.basins[label].low_area.contains(&pos) {
+                    ";7"
+                } else {
+                    ""
+                };
+                format!("{}{}{}", colour, bold, low)
+            }
+            None => String::from("90"),
+        };
+        display.push_str(&format!("\x1b[{}m{}", style, height));
+        if pos.1 + 1 == map.width() {
+            display.push_str("\x1b[0m\n");
         }
-        (0, j) => HashMap::from([
-            ((i + 1, j), map[i + 1][j]),
-            ((i, j - 1), map[i][j - 1]),
-            ((i, j + 1), map[i][j + 1]),
-        ]),
-        (i, 0) => HashMap::from([
-            ((i - 1, j), map[i - 1][j]),
-            ((i + 1, j), map[i + 1][j]),
-            ((i, j + 1), map[i][j + 1]),
-        ]),
-        (i, j) if i == i_len => HashMap::from([
-            ((i - 1, j), map[i - 1][j]),
-            ((i, j - 1), map[i][j - 1]),
-            ((i, j + 1), map[i][j + 1]),
-        ]),
-        (i, j) if j == j_len => HashMap::from([
-            ((i - 1, j), map[i - 1][j]),
-            ((i + 1, j), map[i + 1][j]),
-            ((i, j - 1), map[i][j - 1]),
-        ]),
-        _ => HashMap::from([
-            ((i - 1, j), map[i - 1][j]),
-            ((i + 1, j), map[i + 1][j]),
-            ((i, j - 1), map[i][j - 1]),
-            ((i, j + 1), map[i][j + 1]),
-        ]),
     }
+    display
+}
+
+/// Draws the heightmap as a PPM image, a pixel per cell. Basins get a colour
+/// darker as the cells get higher, at half brightness when they aren't one of
+/// the three largest. Walls are black and low points white
+pub fn render_ppm(map: &Grid<u32>, basins: &Basins) -> Vec<u8> {
+    let largest = largest_basins(basins, 3);
+    let mut image = format!("P6\n{} {}\n255\n", map.width(), map.height()).into_bytes();
+    for (pos, height) in map.iter() {
+        let pixel = match basins.labels[pos] {
+            Some(label) if basins.basins[label].low_area.contains(&pos) => [255; 3],
+            Some(label) => {
+                let shade = 10 - (*height).min(9) as u16;
+                let shade = if largest.contains(&label) {
+                    shade
+                } else {
+                    shade / 2
+                };
+                RGB_COLOURS[label % RGB_COLOURS.len()].map(|c| (c as u16 * shade / 10) as u8)
+            }
+            None => [0; 3],
+        };
+        image.extend(pixel);
+    }
+    image
+}
+
+fn is_low_point(map: &Grid<u32>, pos: Pos, connectivity: Connectivity) -> bool {
+    map.neighbours(pos, connectivity).all(|k| map[k] > map[pos])
 }
 
 #[cfg(test)]
@@ -156,32 +351,235 @@ mod tests_day9 {
     }
 
     #[test]
-    fn test_is_lowest_adjacent() -> Result<()> {
+    fn test_is_low_point() -> Result<()> {
         let map = Day9::parse_input(INPUT)?;
-        assert!(!is_lowest_adjacent(&2, 0, 0, &map));
-        assert!(is_lowest_adjacent(&0, 0, 9, &map));
-        assert!(!is_lowest_adjacent(&9, 4, 0, &map));
-        assert!(!is_lowest_adjacent(&8, 4, 9, &map));
-        assert!(is_lowest_adjacent(&1, 0, 1, &map));
-        assert!(is_lowest_adjacent(&5, 4, 6, &map));
-        assert!(!is_lowest_adjacent(&8, 3, 0, &map));
-        assert!(!is_lowest_adjacent(&2, 2, 9, &map));
-        assert!(is_lowest_adjacent(&5, 2, 2, &map));
+        assert!(!is_low_point(&map, (0, 0), Connectivity::Four));
+        assert!(is_low_point(&map, (0, 9), Connectivity::Four));
+        assert!(!is_low_point(&map, (4, 0), Connectivity::Four));
+        assert!(!is_low_point(&map, (4, 9), Connectivity::Four));
+        assert!(is_low_point(&map, (0, 1), Connectivity::Four));
+        assert!(is_low_point(&map, (4, 6), Connectivity::Four));
+        assert!(!is_low_point(&map, (3, 0), Connectivity::Four));
+        assert!(!is_low_point(&map, (2, 9), Connectivity::Four));
+        assert!(is_low_point(&map, (2, 2), Connectivity::Four));
         Ok(())
     }
 
     #[test]
     fn test_vasin_size() -> Result<()> {
         let map = Day9::parse_input(INPUT)?;
-        let size = basin_size(0, 1, &map);
-        assert_eq!(size, 3);
-        let size = basin_size(0, 9, &map);
-        assert_eq!(size, 9);
-        let size = basin_size(2, 2, &map);
-        assert_eq!(size, 14);
-        let size = basin_size(4, 6, &map);
-        assert_eq!(size, 9);
+        let basins = label_basins(&map, &BasinConfig::default());
+        let size = |low_point: Pos| {
+            let basin = basins.basins.iter().find(|b| b.low_point == low_point);
+            basin.map(|b| b.size())
+        };
+        assert_eq!(size((0, 1)), Some(3));
+        assert_eq!(size((0, 9)), Some(9));
+        assert_eq!(size((2, 2)), Some(14));
+        assert_eq!(size((4, 6)), Some(9));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_label_basins() -> Result<()> {
+        let map = Day9::parse_input(INPUT)?;
+        let basins = label_basins(&map, &BasinConfig::default());
+        assert_eq!(basins.basins.len(), 4);
+        let label = basins.labels[(0, 0)].unwrap();
+        assert_eq!(basins.basins[label].low_point, (0, 1));
+        assert_eq!(basins.labels[(1, 0)], Some(label));
+        assert_eq!(basins.labels[(0, 2)], None);
+        let basin = &basins.basins[label];
+        let mut cells = basin.cells.clone();
+        cells.sort_unstable();
+        assert_eq!(cells, vec![(0, 0), (0, 1), (1, 0)]);
+        let labeled = basins.labels.iter().filter(|(_, l)| l.is_some()).count();
+        let total: usize = basins.basins.iter().map(|b| b.size()).sum();
+        assert_eq!(labeled, total);
+        Ok(())
+    }
+
+    #[test]
+    fn test_label_big_basin() -> Result<()> {
+        // A single snake shaped basin, deep enough to overflow a recursive fill
+        let rows: Vec<Vec<u32>> = (0..1000)
+            .map(|i| match i % 4 {
+                1 => (0..500).map(|j| if j == 499 { 1 } else { 9 }).collect(),
+                3 => (0..500).map(|j| if j == 0 { 1 } else { 9 }).collect(),
+                _ => vec![1; 500],
+            })
+            .collect();
+        let mut map = Grid::from_rows(rows)?;
+        map[(0, 0)] = 0;
+        let basins = label_basins(&map, &BasinConfig::default());
+        assert_eq!(basins.basins.len(), 1);
+        assert_eq!(basins.basins[0].size(), 500 * 500 + 500);
+        Ok(())
+    }
+
+    #[test]
+    fn test_flow_basins() -> Result<()> {
+        let map = Day9::parse_input(INPUT)?;
+        let config = BasinConfig {
+            rule: BasinRule::Flow,
+            ..Default::default()
+        };
+        assert!(drainage_violations(&map, &config).is_empty());
+        let walls = label_basins(&map, &BasinConfig::default());
+        let flow = label_basins(&map, &config);
+        assert_eq!(walls.labels, flow.labels);
+        let sizes: Vec<usize> = flow.basins.iter().map(|b| b.size()).collect();
+        assert_eq!(sizes, vec![3, 9, 14, 9]);
+        Ok(())
+    }
+
+    #[test]
+    fn test_drainage_violations() -> Result<()> {
+        let map = Grid::parse_digits(
+            "12145
+99955
+",
+        )?;
+        let config = BasinConfig::default();
+        let violations = drainage_violations(&map, &config);
+        // The 2 goes down to both 1, the 5 on the right can't go down
+        assert_eq!(
+            violations,
+            vec![((0, 1), Drain::Many), ((1, 4), Drain::Nowhere)]
+        );
+        // By walls the three first cells are one basin
+        let basins = label_basins(&map, &config);
+        assert_eq!(basins.basins.len(), 1);
+        assert_eq!(basins.basins[0].size(), 7);
+        // By flow the 2 isn't in any basin
+        let config = BasinConfig {
+            rule: BasinRule::Flow,
+            ..Default::default()
+        };
+        let basins = label_basins(&map, &config);
+        let sizes: Vec<usize> = basins.basins.iter().map(|b| b.size()).collect();
+        assert_eq!(sizes, vec![1, 4]);
+        assert_eq!(basins.labels[(0, 1)], None);
+        Ok(())
+    }
 
+    #[test]
+    fn test_basin_config() -> Result<()> {
+        let map = Grid::parse_digits(
+            "0590
+5959
+9950
+",
+        )?;
+        let basins = label_basins(&map, &BasinConfig::default());
+        assert_eq!(basins.basins.len(), 3);
+        let config = BasinConfig {
+            connectivity: Connectivity::Eight,
+            ..Default::default()
+        };
+        let basins = label_basins(&map, &config);
+        let sizes: Vec<usize> = basins.basins.iter().map(|b| b.size()).collect();
+        assert_eq!(sizes, vec![7]);
+        let config = BasinConfig {
+            wall: 5,
+            ..Default::default()
+        };
+        let basins = label_basins(&map, &config);
+        let sizes: Vec<usize> = basins.basins.iter().map(|b| b.size()).collect();
+        assert_eq!(sizes, vec![1, 1, 1]);
+        Ok(())
+    }
+
+    #[test]
+    fn test_largest_basins() -> Result<()> {
+        let map = Day9::parse_input(INPUT)?;
+        let basins = label_basins(&map, &BasinConfig::default());
+        let largest = largest_basins(&basins, 3);
+        let low_points: Vec<Pos> = largest
+            .iter()
+            .map(|l| basins.basins[*l].low_point)
+            .collect();
+        assert_eq!(low_points, vec![(2, 2), (0, 9), (4, 6)]);
+        Ok(())
+    }
+
+    #[test]
+    fn test_render_ansi() -> Result<()> {
+        let map = Grid::parse_digits("0192\n9299\n")?;
+        let basins = label_basins(&map, &BasinConfig::default());
+        let display = render_ansi(&map, &basins);
+        assert_eq!(
+            display,
+            "\x1b[31;1;7m0\x1b[31;1m1\x1b[90m9\x1b[32;1;7m2\x1b[0m\n\
+             \x1b[90m9\x1b[31;1m2\x1b[90m9\x1b[90m9\x1b[0m\n"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_render_ppm() -> Result<()> {
+        let map = Grid::parse_digits("019\n")?;
+        let basins = label_basins(&map, &BasinConfig::default());
+        let image = render_ppm(&map, &basins);
+        let header = b"P6\n3 1\n255\n";
+        assert_eq!(&image[..header.len()], header);
+        assert_eq!(
+            &image[header.len()..],
+            &[255, 255, 255, 198, 45, 42, 0, 0, 0]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_plateaus() -> Result<()> {
+        let map = Grid::parse_digits("11929\n12999\n99911\n")?;
+        // Only the 2 at (0, 3) is a strict low point, the 1s are on plateaus
+        let basins = label_basins(&map, &BasinConfig::default());
+        let low_points: Vec<Pos> = basins.basins.iter().map(|b| b.low_point).collect();
+        assert_eq!(low_points, vec![(0, 3)]);
+
+        let config = BasinConfig {
+            plateaus: true,
+            ..Default::default()
+        };
+        let areas = low_areas(&map, &config);
+        assert_eq!(
+            areas,
+            vec![
+                vec![(0, 0), (0, 1), (1, 0)],
+                vec![(0, 3)],
+                vec![(2, 3), (2, 4)]
+            ]
+        );
+        let basins = label_basins(&map, &config);
+        let sizes: Vec<usize> = basins.basins.iter().map(|b| b.size()).collect();
+        assert_eq!(sizes, vec![4, 1, 2]);
+        assert_eq!(basins.basins[0].low_area, areas[0]);
+
+        let config = BasinConfig {
+            rule: BasinRule::Flow,
+            ..config
+        };
+        assert!(drainage_violations(&map, &config).is_empty());
+        let flow = label_basins(&map, &config);
+        assert_eq!(flow.labels, basins.labels);
+        Ok(())
+    }
+
+    #[test]
+    fn test_plateau_scores() -> Result<()> {
+        let map = Grid::parse_digits("11929\n12999\n99911\n")?;
+        let config = BasinConfig::default();
+        assert_eq!(risk_level(&map, &config), 3);
+        assert_eq!(basin_product(&map, &config), 1);
+        // The two plateaus of 1 count once each
+        let config = BasinConfig {
+            plateaus: true,
+            ..Default::default()
+        };
+        assert_eq!(risk_level(&map, &config), 2 + 3 + 2);
+        assert_eq!(basin_product(&map, &config), 4 * 2);
         Ok(())
     }
 }
@@ -0,0 +1,267 @@
+// Shared by the grid puzzles
+
+use std::fmt::Display;
+use std::ops::{Index, IndexMut};
+
+use anyhow::{bail, Result};
+
+/// Position on a grid as (row, column)
+pub type Pos = (usize, usize);
+
+/// Which cells count as neighbours of a cell
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Connectivity {
+    /// Up, left, right and down
+    Four,
+    /// The four ones plus the diagonals
+    Eight,
+}
+
+const FOUR: [(isize, isize); 4] = [(-1, 0), (0, -1), (0, 1), (1, 0)];
+const EIGHT: [(isize, isize); 8] = [
+    (-1, -1),
+    (-1, 0),
+    (-1, 1),
+    (0, -1),
+    (0, 1),
+    (1, -1),
+    (1, 0),
+    (1, 1),
+];
+
+/// Rectangular grid stored row by row
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Grid<T> {
+    width: usize,
+    height: usize,
+    cells: Vec<T>,
+}
+
+impl<T> Grid<T> {
+    /// Builds the grid from its rows, all of them must have the same length
+    pub fn from_rows(rows: Vec<Vec<T>>) -> Result<Self> {
+        let height = rows.len();
+        let width = rows.first().map_or(0, |r| r.len());
+        let mut cells = Vec::with_capacity(width * height);
+        for (i, row) in rows.into_iter().enumerate() {
+            if row.len() != width {
+                bail!("row {} has {} cells, expected {}", i, row.len(), width);
+            }
+            cells.extend(row);
+        }
+        Ok(Self {
+            width,
+            height,
+            cells,
+        })
+    }
+
+    pub fn filled(width: usize, height: usize, value: T) -> Self
+    where
+        T: Clone,
+    {
+        Self {
+            width,
+            height,
+            cells: vec![value; width * height],
+        }
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    pub fn get(&self, (row, column): Pos) -> Option<&T> {
+        match row < self.height && column < self.width {
+            true => self.cells.get(row * self.width + column),
+            false => None,
+        }
+    }
+
+    pub fn get_mut(&mut self, (row, column): Pos) -> Option<&mut T> {
+        match row < self.height && column < self.width {
+            true => self.cells.get_mut(row * self.width + column),
+            false => None,
+        }
+    }
+
+    /// Positions next to `pos` that are inside the grid
+    pub fn neighbours(
+        &self,
+        (row, column): Pos,
+        connectivity: Connectivity,
+    ) -> impl Iterator<Item = Pos> + '_ {
+        let offsets: &[(isize, isize)] = match connectivity {
+            Connectivity::Four => &FOUR,
+            Connectivity::Eight => &EIGHT,
+        };
+        offsets.iter().filter_map(move |(i, j)| {
+            let row = row.checked_add_signed(*i)?;
+            let column = column.checked_add_signed(*j)?;
+            match row < self.height && column < self.width {
+                true => Some((row, column)),
+                false => None,
+            }
+        })
+    }
+
+    /// Every position, row by row
+    pub fn positions(&self) -> impl Iterator<Item = Pos> {
+        let width = self.width;
+        (0..self.height).flat_map(move |row| (0..width).map(move |column| (row, column)))
+    }
+
+    /// Every cell with its position, row by row
+    pub fn iter(&self) -> impl Iterator<Item = (Pos, &T)> {
+        self.positions().zip(self.cells.iter())
+    }
+
+    pub fn rows(&self) -> impl Iterator<Item = &[T]> {
+        self.cells.chunks(self.width.max(1))
+    }
+
+    // No puzzle goes through the columns yet
+    #[allow(dead_code)]
+    pub fn column(&self, column: usize) -> impl Iterator<Item = &T> {
+        self.cells
+            .iter()
+            .skip(column)
+            .step_by(self.width.max(1))
+            .take(if column < self.width { self.height } else { 0 })
+    }
+
+    #[allow(dead_code)]
+    pub fn columns(&self) -> impl Iterator<Item = impl Iterator<Item = &T>> {
+        (0..self.width).map(move |column| self.column(column))
+    }
+
+    pub fn map<U>(&self, f: impl Fn(&T) -> U) -> Grid<U> {
+        Grid {
+            width: self.width,
+            height: self.height,
+            cells: self.cells.iter().map(f).collect(),
+        }
+    }
+}
+
+impl Grid<u32> {
+    /// Parses a grid of single digits, one row per line
+    pub fn parse_digits(input: &str) -> Result<Self> {
+        let rows = input
+            .lines()
+            .filter(|l| !l.trim().is_empty())
+            .enumerate()
+            .map(|(i, l)| {
+                l.trim()
+                    .chars()
+                    .map(|c| {
+                        c.to_digit(10)
+                            .ok_or(anyhow::anyhow!("row {}: invalid digit {:?}", i, c))
+                    })
+                    .collect()
+            })
+            .collect::<Result<_>>()?;
+        Self::from_rows(rows)
+    }
+}
+
+impl Grid<char> {
+    /// Parses a grid of characters, one row per line
+    pub fn parse_chars(input: &str) -> Result<Self> {
+        let rows = input
+            .lines()
+            .filter(|l| !l.trim().is_empty())
+            .map(|l| l.trim().chars().collect())
+            .collect();
+        Self::from_rows(rows)
+    }
+}
+
+impl<T> Index<Pos> for Grid<T> {
+    type Output = T;
+
+    fn index(&self, pos: Pos) -> &T {
+        self.get(pos).expect("position out of the grid")
+    }
+}
+
+impl<T> IndexMut<Pos> for Grid<T> {
+    fn index_mut(&mut self, pos: Pos) -> &mut T {
+        self.get_mut(pos).expect("position out of the grid")
+    }
+}
+
+// Each row on a line with the cells side by side
+impl<T: Display> Display for Grid<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for row in self.rows().take(self.height) {
+            row.iter().try_for_each(|c| write!(f, "{}", c))?;
+            writeln!(f)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests_grid {
+    use super::*;
+
+    const INPUT: &str = "123\n456\n";
+
+    #[test]
+    fn test_parse_digits() -> Result<()> {
+        let grid = Grid::parse_digits(INPUT)?;
+        assert_eq!(grid.width(), 3);
+        assert_eq!(grid.height(), 2);
+        assert_eq!(grid[(1, 0)], 4);
+        assert_eq!(grid.get((0, 2)), Some(&3));
+        assert_eq!(grid.get((2, 0)), None);
+        assert_eq!(grid.get((0, 3)), None);
+        assert_eq!(grid.to_string(), INPUT);
+        assert!(Grid::parse_digits("12\n3").is_err());
+        assert!(Grid::parse_digits("1a").is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_parse_chars() -> Result<()> {
+        let mut grid = Grid::parse_chars("#.\n.#\n")?;
+        assert_eq!(grid[(1, 1)], '#');
+        grid[(1, 1)] = '.';
+        assert_eq!(grid.to_string(), "#.\n..\n");
+        Ok(())
+    }
+
+    #[test]
+    fn test_neighbours() -> Result<()> {
+        let grid = Grid::parse_digits(INPUT)?;
+        let n: Vec<Pos> = grid.neighbours((0, 0), Connectivity::Four).collect();
+        assert_eq!(n, vec![(0, 1), (1, 0)]);
+        let n: Vec<Pos> = grid.neighbours((0, 1), Connectivity::Four).collect();
+        assert_eq!(n, vec![(0, 0), (0, 2), (1, 1)]);
+        let n: Vec<Pos> = grid.neighbours((1, 1), Connectivity::Eight).collect();
+        assert_eq!(n, vec![(0, 0), (0, 1), (0, 2), (1, 0), (1, 2)]);
+        Ok(())
+    }
+
+    #[test]
+    fn test_rows_and_columns() -> Result<()> {
+        let grid = Grid::parse_digits(INPUT)?;
+        let rows: Vec<&[u32]> = grid.rows().collect();
+        assert_eq!(rows, vec![&[1, 2, 3][..], &[4, 5, 6][..]]);
+        let column: Vec<u32> = grid.column(1).copied().collect();
+        assert_eq!(column, vec![2, 5]);
+        assert_eq!(grid.column(3).count(), 0);
+        let sums: Vec<u32> = grid.columns().map(|c| c.sum()).collect();
+        assert_eq!(sums, vec![5, 7, 9]);
+        let cells: Vec<(Pos, u32)> = grid.iter().map(|(p, c)| (p, *c)).collect();
+        assert_eq!(cells[4], ((1, 1), 5));
+        assert_eq!(grid.map(|c| c * 2)[(1, 2)], 12);
+        assert_eq!(Grid::filled(2, 3, 0).height(), 3);
+        Ok(())
+    }
+}
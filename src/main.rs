@@ -1,5 +1,21 @@
 mod day_1;
+mod day_10;
+mod day_11;
+mod day_12;
+mod day_13;
+mod day_14;
+mod day_15;
+mod day_16;
+mod day_17;
+mod day_18;
+mod day_19;
 mod day_2;
+mod day_20;
+mod day_21;
+mod day_22;
+mod day_23;
+mod day_24;
+mod day_25;
 mod day_3;
 mod day_4;
 mod day_5;
@@ -7,22 +23,86 @@ mod day_6;
 mod day_7;
 mod day_8;
 mod day_9;
+mod grid;
 mod runner;
 
-use anyhow::Result;
+use anyhow::{bail, Result};
 use day_1::Day1;
+use day_10::Day10;
+use day_11::Day11;
+use day_12::Day12;
+use day_13::Day13;
+use day_14::Day14;
+use day_15::Day15;
+use day_16::Day16;
+use day_17::Day17;
+use day_18::Day18;
+use day_19::Day19;
 use day_2::Day2;
-use day_3::Day3;
+use day_20::Day20;
+use day_21::Day21;
+use day_22::Day22;
+use day_23::Day23;
+use day_24::Day24;
+use day_25::Day25;
+use day_3::{Day3, Policy};
 use day_4::Day4;
 use day_5::Day5;
-use day_6::Day6;
-use day_7::Day7;
-use day_8::Day8;
-use day_9::Day9;
+use day_6::{Arithmetic, Day6, LifeCycle};
+use day_7::{Crabs, Day7, Linear, Triangular};
+use day_8::{Day8, Mode};
+use day_9::{BasinConfig, BasinRule, Day9};
+use grid::Connectivity;
 use runner::Executor;
 use runner::MutExecutor;
+use runner::Parse;
+use runner::StreamExecutor;
+
+const USAGE: &str = "usage: aoc-2021 [command]
+commands:
+    day1 stream <path>
+    day2 stream <path>
+    day2 path [--json | --plot <width> [--aim]]
+    day3 rates [--ties <gamma>,<epsilon>,<oxygen>,<co2>]
+    day3 prefixes <length> [k]
+    day5 stream <path>
+    day6 fast <days> [modulo] [--cycle <reset>,<newborn>]
+    day6 series <days> [--naive] [--cycle <reset>,<newborn>]
+    day7 curve [--spark <width>]
+    day8 render [entry]
+    day8 check [--strict]
+    day9 basins [--wall <height>] [--eight] [--flow] [--plateaus]
+    day9 render [--ppm <path>] [--wall <height>] [--eight] [--flow] [--plateaus]";
 
 fn main() -> Result<()> {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    let args: Vec<&str> = args.iter().map(String::as_str).collect();
+    match args.as_slice() {
+        [] => run_all(),
+        ["day1", "stream", path] => Day1::run_stream(path).map(|_| ()),
+        ["day2", "stream", path] => Day2::run_stream(path).map(|_| ()),
+        ["day2", "path", rest @ ..] => day2_path(rest.to_vec()),
+        ["day3", "rates", rest @ ..] => day3_rates(rest.to_vec()),
+        ["day3", "prefixes", length] => day3_prefixes(length.parse()?, None),
+        ["day3", "prefixes", length, k] => day3_prefixes(length.parse()?, Some(k.parse()?)),
+        ["day5", "stream", path] => Day5::run_stream(path).map(|_| ()),
+        ["day6", "fast", rest @ ..] => day6_fast(rest.to_vec()),
+        ["day6", "series", rest @ ..] => day6_series(rest.to_vec()),
+        ["day7", "curve", rest @ ..] => day7_curve(rest.to_vec()),
+        ["day8", "render"] => day8_render(None),
+        ["day8", "render", entry] => day8_render(Some(entry.parse()?)),
+        ["day8", "check"] => day8_check(Mode::Lenient),
+        ["day8", "check", "--strict"] => day8_check(Mode::Strict),
+        ["day9", "basins", rest @ ..] => day9_basins(rest.to_vec()),
+        ["day9", "render", rest @ ..] => day9_render(rest.to_vec()),
+        _ => bail!(USAGE),
+    }
+}
+
+/// Runs a day on the input at the path
+type Solver = fn(&str) -> Result<()>;
+
+fn run_all() -> Result<()> {
     Day1::run("inputs/day1.input")?;
     Day2::run("inputs/day2.input")?;
     Day3::run("inputs/day3.input")?;
@@ -32,5 +112,261 @@ fn main() -> Result<()> {
     Day7::run("inputs/day7.input")?;
     Day8::run("inputs/day8.input")?;
     Day9::run("inputs/day9.input")?;
+    // Later days only run when their input is there
+    let later: &[(&str, Solver)] = &[
+        ("inputs/day10.input", |p| Day10::run(p).map(|_| ())),
+        ("inputs/day11.input", |p| Day11::run(p).map(|_| ())),
+        ("inputs/day12.input", |p| Day12::run(p).map(|_| ())),
+        ("inputs/day13.input", |p| Day13::run(p).map(|_| ())),
+        ("inputs/day14.input", |p| Day14::run(p).map(|_| ())),
+        ("inputs/day15.input", |p| Day15::run(p).map(|_| ())),
+        ("inputs/day16.input", |p| Day16::run(p).map(|_| ())),
+        ("inputs/day17.input", |p| Day17::run(p).map(|_| ())),
+        ("inputs/day18.input", |p| Day18::run(p).map(|_| ())),
+        ("inputs/day19.input", |p| Day19::run(p).map(|_| ())),
+        ("inputs/day20.input", |p| Day20::run(p).map(|_| ())),
+        ("inputs/day21.input", |p| Day21::run(p).map(|_| ())),
+        ("inputs/day22.input", |p| Day22::run(p).map(|_| ())),
+        ("inputs/day23.input", |p| Day23::run(p).map(|_| ())),
+        ("inputs/day24.input", |p| Day24::run(p).map(|_| ())),
+        ("inputs/day25.input", |p| Day25::run(p).map(|_| ())),
+    ];
+    for (path, run) in later {
+        if std::path::Path::new(path).exists() {
+            run(path)?;
+        }
+    }
+    Ok(())
+}
+
+/// Removes `name` and its value from the arguments, returning the value
+fn take_option<'a>(args: &mut Vec<&'a str>, name: &str) -> Result<Option<&'a str>> {
+    match args.iter().position(|a| *a == name) {
+        Some(i) if i + 1 < args.len() => {
+            let value = args.remove(i + 1);
+            args.remove(i);
+            Ok(Some(value))
+        }
+        Some(_) => bail!("missing value for {}", name),
+        None => Ok(None),
+    }
+}
+
+/// Removes the `name` flag from the arguments, returning if it was there
+fn take_flag(args: &mut Vec<&str>, name: &str) -> bool {
+    let len = args.len();
+    args.retain(|a| *a != name);
+    args.len() != len
+}
+
+fn day6_cycle(args: &mut Vec<&str>) -> Result<LifeCycle> {
+    match take_option(args, "--cycle")? {
+        Some(c) => c.parse(),
+        None => Ok(LifeCycle::default()),
+    }
+}
+
+fn day6_fast(mut args: Vec<&str>) -> Result<()> {
+    let cycle = day6_cycle(&mut args)?;
+    let (days, arithmetic): (u64, _) = match args.as_slice() {
+        [days] => (days.parse()?, Arithmetic::Exact),
+        [days, modulo] => (days.parse()?, Arithmetic::Modulo(modulo.parse()?)),
+        _ => bail!(USAGE),
+    };
+    let input = std::fs::read_to_string("inputs/day6.input")?;
+    let input = cycle.parse_fish(&input)?;
+    let count = day_6::fast_count_fish(&input, days, &cycle, arithmetic)?;
+    println!("inputs/day6.input after {} days: {}", days, count);
+    Ok(())
+}
+
+fn day2_path(mut args: Vec<&str>) -> Result<()> {
+    let json = take_flag(&mut args, "--json");
+    let plot = take_option(&mut args, "--plot")?;
+    let aim = take_flag(&mut args, "--aim");
+    if !args.is_empty() || (json && plot.is_some()) {
+        bail!(USAGE);
+    }
+    let input = std::fs::read_to_string("inputs/day2.input")?;
+    let path = day_2::course_path(&Day2::parse_input(&input)?)?;
+    match (json, plot) {
+        (true, _) => print!("{}", day_2::path_to_json(&path)),
+        (false, Some(width)) => {
+            let states: Vec<_> = match aim {
+                true => path.iter().map(|s| s.aim).collect(),
+                false => path.iter().map(|s| s.simple).collect(),
+            };
+            print!("{}", day_2::depth_profile(&states, width.parse()?, 16));
+        }
+        (false, None) => print!("{}", day_2::path_to_csv(&path)),
+    }
+    Ok(())
+}
+
+fn day3_rates(mut args: Vec<&str>) -> Result<()> {
+    let policy: Policy = match take_option(&mut args, "--ties")? {
+        Some(ties) => ties.parse()?,
+        None => Policy::default(),
+    };
+    if !args.is_empty() {
+        bail!(USAGE);
+    }
+    let input = std::fs::read_to_string("inputs/day3.input")?;
+    let report = Day3::parse_input(&input)?;
+    let width = report.width();
+    let (gamma, epsilon) = report.power_rates(&policy)?;
+    let (oxygen, co2) = report.life_support(&policy)?;
+    for (name, rate) in [
+        ("gamma", gamma),
+        ("epsilon", epsilon),
+        ("oxygen", oxygen),
+        ("co2", co2),
+    ] {
+        println!("{:<8} {:0width$b} {}", name, rate, rate);
+    }
+    Ok(())
+}
+
+fn day3_prefixes(length: usize, k: Option<usize>) -> Result<()> {
+    let input = std::fs::read_to_string("inputs/day3.input")?;
+    let trie = Day3::parse_input(&input)?.trie()?;
+    let prefixes = match k {
+        Some(k) => trie.kth_prefix(length, k)?.into_iter().collect(),
+        None => trie.prefixes(length)?,
+    };
+    for (prefix, count) in prefixes {
+        println!("{:0length$b} {}", prefix, count);
+    }
+    Ok(())
+}
+
+fn day6_series(mut args: Vec<&str>) -> Result<()> {
+    let cycle = day6_cycle(&mut args)?;
+    let naive = take_flag(&mut args, "--naive");
+    let days: usize = match args.as_slice() {
+        [days] => days.parse()?,
+        _ => bail!(USAGE),
+    };
+    let input = std::fs::read_to_string("inputs/day6.input")?;
+    let series = day_6::population_series(&cycle.parse_fish(&input)?, days, &cycle);
+    let naive = match naive {
+        true => Some(day_6::naive_population_series(
+            &cycle.parse_timers(&input)?,
+            days,
+            &cycle,
+        )),
+        false => None,
+    };
+    print!("{}", day_6::series_to_csv(&series, naive.as_deref()));
+    Ok(())
+}
+
+fn day7_curve(mut args: Vec<&str>) -> Result<()> {
+    let spark = take_option(&mut args, "--spark")?;
+    if !args.is_empty() {
+        bail!(USAGE);
+    }
+    let input = std::fs::read_to_string("inputs/day7.input")?;
+    let crabs = Crabs::new(&Day7::parse_input(&input)?);
+    match spark {
+        Some(width) => {
+            let width = width.parse()?;
+            let linear = crabs.align(&Linear)?;
+            let spark = day_7::sparkline(&crabs.fuel_curve(&Linear), width);
+            println!("linear     {} best {:?}", spark, linear);
+            let triangular = crabs.align(&Triangular)?;
+            let spark = day_7::sparkline(&crabs.fuel_curve(&Triangular), width);
+            println!("triangular {} best {:?}", spark, triangular);
+        }
+        None => print!("{}", crabs.curve_to_csv()),
+    }
+    Ok(())
+}
+
+fn day8_render(entry: Option<usize>) -> Result<()> {
+    let input = std::fs::read_to_string("inputs/day8.input")?;
+    let metrics = Day8::parse_input(&input)?;
+    for (i, metric) in metrics.iter().enumerate() {
+        if entry.is_none_or(|e| e == i + 1) {
+            println!("entry {}", i + 1);
+            println!("{}", day_8::render_metric(metric));
+        }
+    }
+    Ok(())
+}
+
+fn day8_check(mode: Mode) -> Result<()> {
+    let input = std::fs::read_to_string("inputs/day8.input")?;
+    let metrics = Day8::parse_input(&input)?;
+    for (line, value) in day_8::decode_entries(&metrics) {
+        match value {
+            Ok(value) => println!("line {}: {}", line, value),
+            Err(e) => println!("line {}: {}", line, e),
+        }
+    }
+    println!("sum: {}", day_8::sum_outputs(&metrics, mode)?);
+    Ok(())
+}
+
+fn day9_config(args: &mut Vec<&str>) -> Result<BasinConfig> {
+    let mut config = BasinConfig::default();
+    if let Some(wall) = take_option(args, "--wall")? {
+        config.wall = wall.parse()?;
+    }
+    if take_flag(args, "--eight") {
+        config.connectivity = Connectivity::Eight;
+    }
+    if take_flag(args, "--flow") {
+        config.rule = BasinRule::Flow;
+    }
+    if take_flag(args, "--plateaus") {
+        config.plateaus = true;
+    }
+    Ok(config)
+}
+
+fn day9_basins(mut args: Vec<&str>) -> Result<()> {
+    let config = day9_config(&mut args)?;
+    if !args.is_empty() {
+        bail!(USAGE);
+    }
+    let input = std::fs::read_to_string("inputs/day9.input")?;
+    let map = Day9::parse_input(&input)?;
+    let basins = day_9::label_basins(&map, &config);
+    let mut sizes: Vec<usize> = basins.basins.iter().map(|b| b.size()).collect();
+    sizes.sort_unstable();
+    let largest: Vec<usize> = sizes.into_iter().rev().take(3).collect();
+    println!("basins: {}", basins.basins.len());
+    println!("largest: {:?}", largest);
+    println!("risk level: {}", day_9::risk_level(&map, &config));
+    println!(
+        "product of the largest: {}",
+        day_9::basin_product(&map, &config)
+    );
+    let violations = day_9::drainage_violations(&map, &config);
+    println!(
+        "cells not draining to exactly one low point: {}",
+        violations.len()
+    );
+    for (pos, drain) in violations {
+        println!("  {:?}: {:?}", pos, drain);
+    }
+    Ok(())
+}
+
+fn day9_render(mut args: Vec<&str>) -> Result<()> {
+    let config = day9_config(&mut args)?;
+    let ppm = take_option(&mut args, "--ppm")?;
+    if !args.is_empty() {
+        bail!(USAGE);
+    }
+    let input = std::fs::read_to_string("inputs/day9.input")?;
+    let map = Day9::parse_input(&input)?;
+    let basins = day_9::label_basins(&map, &config);
+    print!("{}", day_9::render_ansi(&map, &basins));
+    if let Some(path) = ppm {
+        std::fs::write(path, day_9::render_ppm(&map, &basins))?;
+        println!("image written to {}", path);
+    }
     Ok(())
 }
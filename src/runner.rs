@@ -1,4 +1,6 @@
 use std::fmt::Display;
+use std::fs::File;
+use std::io::{BufRead, BufReader};
 
 use anyhow::Result;
 
@@ -11,15 +13,16 @@ pub trait Parse<I> {
     fn parse_input(input: &str) -> Result<I>;
 }
 
-pub trait ParseWithLifeTime<'a, I> {
-    fn parse_input(input: &'a str) -> Result<I>;
-}
-
 pub trait RunMut<I, R> {
     fn part_one(input: &mut I) -> Result<R>;
     fn part_two(input: &mut I) -> Result<R>;
 }
 
+pub trait RunStream<R> {
+    /// Solves both parts reading the input once, line by line
+    fn run_lines(input: impl BufRead) -> Result<(R, R)>;
+}
+
 // There's gotta be better way to implement this, than to have a traits per implementation
 
 pub trait Executor<I, R, T>
@@ -69,3 +72,24 @@ where
         Ok((r1, r2))
     }
 }
+
+pub trait StreamExecutor<R, T>
+where
+    T: RunStream<R>,
+{
+    fn run_stream(path: &str) -> Result<(R, R)>;
+}
+
+impl<R, T> StreamExecutor<R, T> for T
+where
+    T: RunStream<R>,
+    R: Display,
+{
+    fn run_stream(path: &str) -> Result<(R, R)> {
+        let input = BufReader::new(File::open(path)?);
+        let (r1, r2) = <T as RunStream<R>>::run_lines(input)?;
+        println!("{} part 1: {}", path, r1);
+        println!("{} part 2: {}", path, r2);
+        Ok((r1, r2))
+    }
+}